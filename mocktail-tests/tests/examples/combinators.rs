@@ -0,0 +1,85 @@
+use anyhow::Error;
+use mocktail::prelude::*;
+use test_log::test;
+
+#[test(tokio::test)]
+async fn test_combinators() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.get().path("/health");
+        then.text("healthy");
+    });
+    mocks.mock(|when, then| {
+        when.post().any_of(|w| {
+            w.path("/a")
+                .all_of(|w| w.path_prefix("/b").header_exists("x-b"))
+        });
+        then.text("a or b");
+    });
+    mocks.mock_with_options(6, None, |when, then| {
+        when.get()
+            .not(|w| w.path("/health"))
+            .not(|w| w.header_exists("x-debug"));
+        then.text("anything but health");
+    });
+
+    let server = MockServer::new_http("combinators").with_mocks(mocks);
+    server.start().await?;
+
+    let client = reqwest::Client::new();
+
+    let response = client.get(server.url("/health")).send().await?;
+    assert_eq!(response.text().await?, "healthy");
+
+    let response = client.get(server.url("/hello")).send().await?;
+    assert_eq!(response.text().await?, "anything but health");
+
+    let response = client
+        .get(server.url("/hello"))
+        .header("x-debug", "1")
+        .send()
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+
+    let response = client.post(server.url("/a")).send().await?;
+    assert_eq!(response.text().await?, "a or b");
+
+    let response = client
+        .post(server.url("/b/1"))
+        .header("x-b", "1")
+        .send()
+        .await?;
+    assert_eq!(response.text().await?, "a or b");
+
+    let response = client.post(server.url("/b/1")).send().await?;
+    assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[test]
+fn test_combinators_dedup() {
+    let mock = Mock::new(|when, then| {
+        when.not(|w| w.path("/health"))
+            .not(|w| w.path("/health"))
+            .any_of(|w| w.get().post())
+            .any_of(|w| w.post().get());
+        then.ok();
+    });
+    assert_eq!(mock.matchers.len(), 2);
+    assert!(mock
+        .matchers
+        .contains(&(std::sync::Arc::new(not(path("/health"))) as _)));
+    assert!(mock.matchers.contains(
+        &(std::sync::Arc::new(any_of([method(Method::GET), method(Method::POST)])) as _)
+    ));
+}
+
+#[test]
+#[should_panic(expected = "any_of requires at least one matcher")]
+fn test_any_of_empty() {
+    let _ = Mock::new(|when, then| {
+        when.any_of(|w| w);
+        then.ok();
+    });
+}
@@ -0,0 +1,179 @@
+use anyhow::Error;
+use mocktail::{
+    grpc::{GrpcFrame, GrpcFrameDecoder},
+    prelude::*,
+};
+use mocktail_tests::pb::{HelloRequest, HelloResponse};
+use prost::Message;
+use test_log::test;
+
+#[test(tokio::test)]
+async fn test_connect_unary() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.path("/example.Hello/HelloUnary")
+            .pb(HelloRequest { name: "dan".into() });
+        then.pb(HelloResponse {
+            message: "hello dan!".into(),
+        })
+        .grpc_metadata([("x-request-id", "1")])
+        .grpc_trailers([("x-trailer", "done")]);
+    });
+    mocks.mock(|when, then| {
+        when.path("/example.Hello/HelloUnary")
+            .json(serde_json::json!({"name": "unexpected_error"}));
+        then.grpc_status(Code::Unavailable)
+            .message("try again")
+            .grpc_error_details([ErrorDetail::retry_info(std::time::Duration::from_secs(1))]);
+    });
+    mocks.mock(|when, then| {
+        when.path("/example.Hello/HelloUnary")
+            .json(serde_json::json!({"name": "paulo"}));
+        then.pb(HelloResponse {
+            message: "hello paulo!".into(),
+        });
+    });
+    mocks.mock(|when, then| {
+        when.path("/example.Hello/HelloUnary").pb(HelloRequest {
+            name: "slow".into(),
+        });
+        then.pb(HelloResponse {
+            message: "hello slow!".into(),
+        })
+        .delay(std::time::Duration::from_secs(5));
+    });
+
+    let server = MockServer::new_grpc("example.Hello").with_mocks(mocks);
+    server.start().await?;
+
+    let client = reqwest::Client::new();
+
+    // Protobuf codec, matched by the same mock as gRPC
+    let response = client
+        .post(server.url("/example.Hello/HelloUnary"))
+        .header("content-type", "application/proto")
+        .header("connect-protocol-version", "1")
+        .body(HelloRequest { name: "dan".into() }.encode_to_vec())
+        .send()
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "application/proto"
+    );
+    assert_eq!(response.headers().get("x-request-id").unwrap(), "1");
+    assert_eq!(response.headers().get("trailer-x-trailer").unwrap(), "done");
+    let message = HelloResponse::decode(response.bytes().await?)?;
+    assert_eq!(message.message, "hello dan!");
+
+    // JSON codec error
+    let response = client
+        .post(server.url("/example.Hello/HelloUnary"))
+        .header("content-type", "application/json")
+        .header("connect-protocol-version", "1")
+        .json(&serde_json::json!({"name": "unexpected_error"}))
+        .send()
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::SERVICE_UNAVAILABLE);
+    let error: serde_json::Value = response.json().await?;
+    assert_eq!(error["code"], "unavailable");
+    assert_eq!(error["message"], "try again");
+    assert_eq!(error["details"][0]["type"], "google.rpc.RetryInfo");
+
+    // JSON codec with a protobuf response
+    let response = client
+        .post(server.url("/example.Hello/HelloUnary"))
+        .header("content-type", "application/json")
+        .json(&serde_json::json!({"name": "paulo"}))
+        .send()
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::INTERNAL_SERVER_ERROR);
+    let error: serde_json::Value = response.json().await?;
+    assert_eq!(error["code"], "internal");
+
+    // Deadline exceeded
+    let response = client
+        .post(server.url("/example.Hello/HelloUnary"))
+        .header("content-type", "application/proto")
+        .header("connect-timeout-ms", "100")
+        .body(
+            HelloRequest {
+                name: "slow".into(),
+            }
+            .encode_to_vec(),
+        )
+        .send()
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::GATEWAY_TIMEOUT);
+    let error: serde_json::Value = response.json().await?;
+    assert_eq!(error["code"], "deadline_exceeded");
+
+    // No mock
+    let response = client
+        .post(server.url("/example.Hello/HelloUnary"))
+        .header("content-type", "application/proto")
+        .body(HelloRequest { name: "ian".into() }.encode_to_vec())
+        .send()
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+    let error: serde_json::Value = response.json().await?;
+    assert_eq!(error["code"], "not_found");
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_connect_streaming() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.path("/example.Hello/HelloBidiStreaming")
+            .pb(HelloRequest { name: "dan".into() });
+        then.pb_stream([
+            HelloResponse {
+                message: "hello dan!".into(),
+            },
+            HelloResponse {
+                message: "bye dan!".into(),
+            },
+        ])
+        .grpc_trailers([("x-trailer", "done")]);
+    });
+
+    let server = MockServer::new_grpc("example.Hello").with_mocks(mocks);
+    server.start().await?;
+
+    let client = reqwest::Client::builder().http2_prior_knowledge().build()?;
+    let body = GrpcFrame {
+        compressed: false,
+        data: HelloRequest { name: "dan".into() }.encode_to_vec().into(),
+    }
+    .encode();
+    let response = client
+        .post(server.url("/example.Hello/HelloBidiStreaming"))
+        .header("content-type", "application/connect+proto")
+        .header("connect-protocol-version", "1")
+        .body(body)
+        .send()
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "application/connect+proto"
+    );
+
+    let mut decoder = GrpcFrameDecoder::new();
+    let frames = decoder.decode(response.bytes().await?);
+    assert_eq!(frames.len(), 3);
+    let message = HelloResponse::decode(frames[0].data.clone())?;
+    assert_eq!(message.message, "hello dan!");
+    let message = HelloResponse::decode(frames[1].data.clone())?;
+    assert_eq!(message.message, "bye dan!");
+    // End-of-stream message
+    let end: serde_json::Value = serde_json::from_slice(&frames[2].data)?;
+    assert_eq!(
+        end,
+        serde_json::json!({"metadata": {"x-trailer": ["done"]}})
+    );
+
+    Ok(())
+}
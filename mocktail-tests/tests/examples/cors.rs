@@ -0,0 +1,144 @@
+use std::time::Duration;
+
+use anyhow::Error;
+use mocktail::prelude::*;
+use test_log::test;
+
+#[test(tokio::test)]
+async fn test_cors() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.get().path("/hello").origin("http://localhost:3000");
+        then.text("hello!").headers([("x-request-id", "1")]);
+    });
+    mocks.mock(|when, then| {
+        when.get().path("/compressed");
+        then.text("hello!").headers([("vary", "accept-encoding")]);
+    });
+
+    let server = MockServer::new_http("cors").with_mocks(mocks).with_cors(
+        Cors::new()
+            .with_origins(["http://localhost:3000"])
+            .with_methods(["GET", "POST"])
+            .with_headers(["content-type", "authorization"])
+            .with_expose_headers(["x-request-id"])
+            .with_credentials(true)
+            .with_max_age(Duration::from_secs(600)),
+    );
+    server.start().await?;
+
+    let client = reqwest::Client::new();
+
+    // Preflight
+    let response = client
+        .request(reqwest::Method::OPTIONS, server.url("/hello"))
+        .header("origin", "http://localhost:3000")
+        .header("access-control-request-method", "GET")
+        .header("access-control-request-headers", "authorization")
+        .send()
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::NO_CONTENT);
+    let headers = response.headers();
+    assert_eq!(
+        headers["access-control-allow-origin"],
+        "http://localhost:3000"
+    );
+    assert_eq!(headers["access-control-allow-methods"], "GET, POST");
+    assert_eq!(
+        headers["access-control-allow-headers"],
+        "content-type, authorization"
+    );
+    assert_eq!(headers["access-control-allow-credentials"], "true");
+    assert_eq!(headers["access-control-max-age"], "600");
+
+    // Preflight from a disallowed origin
+    let response = client
+        .request(reqwest::Method::OPTIONS, server.url("/hello"))
+        .header("origin", "http://example.com")
+        .header("access-control-request-method", "GET")
+        .send()
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::NO_CONTENT);
+    assert!(!response
+        .headers()
+        .contains_key("access-control-allow-origin"));
+
+    // Actual request
+    let response = client
+        .get(server.url("/hello"))
+        .header("origin", "http://localhost:3000")
+        .send()
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::OK);
+    let headers = response.headers();
+    assert_eq!(
+        headers["access-control-allow-origin"],
+        "http://localhost:3000"
+    );
+    assert_eq!(headers["access-control-expose-headers"], "x-request-id");
+    assert_eq!(headers["vary"], "origin");
+    assert_eq!(response.text().await?, "hello!");
+
+    // Vary header of the mock is kept
+    let response = client
+        .get(server.url("/compressed"))
+        .header("origin", "http://localhost:3000")
+        .send()
+        .await?;
+    assert_eq!(response.headers()["vary"], "accept-encoding, origin");
+
+    // Not found responses also carry CORS headers
+    let response = client
+        .get(server.url("/world"))
+        .header("origin", "http://localhost:3000")
+        .send()
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+    assert!(response
+        .headers()
+        .contains_key("access-control-allow-origin"));
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_cors_permissive() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.delete().path("/items/1");
+        then.ok();
+    });
+
+    // The last policy is used
+    let server = MockServer::new_http("cors")
+        .with_mocks(mocks)
+        .with_cors(Cors::new().with_origins(["http://localhost:3000"]))
+        .with_cors(Cors::new());
+    server.start().await?;
+
+    let client = reqwest::Client::new();
+
+    let response = client
+        .request(reqwest::Method::OPTIONS, server.url("/items/1"))
+        .header("origin", "http://localhost:5173")
+        .header("access-control-request-method", "DELETE")
+        .header("access-control-request-headers", "x-token")
+        .send()
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::NO_CONTENT);
+    let headers = response.headers();
+    assert_eq!(headers["access-control-allow-origin"], "*");
+    assert_eq!(headers["access-control-allow-methods"], "DELETE");
+    assert_eq!(headers["access-control-allow-headers"], "x-token");
+    assert!(!headers.contains_key("access-control-allow-credentials"));
+
+    let response = client
+        .delete(server.url("/items/1"))
+        .header("origin", "http://localhost:5173")
+        .send()
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(response.headers()["access-control-allow-origin"], "*");
+
+    Ok(())
+}
@@ -0,0 +1,147 @@
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use anyhow::Error;
+use mocktail::prelude::*;
+use test_log::test;
+use tokio::sync::RwLock;
+
+#[test(tokio::test)]
+async fn test_fn_matcher() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.get()
+            .path("/hello")
+            .matches_fn("even_id", |req: &Request| {
+                match req
+                    .headers
+                    .get("x-id")
+                    .and_then(|id| id.parse::<u64>().ok())
+                {
+                    Some(id) if id % 2 == 0 => Ok(()),
+                    Some(id) => Err(format!("id {id} is odd")),
+                    None => Err("missing id".to_string()),
+                }
+            });
+        then.text("even!");
+    });
+
+    let server = MockServer::new_http("fn_matcher").with_mocks(mocks);
+    server.start().await?;
+
+    let client = reqwest::Client::new();
+
+    let response = client
+        .get(server.url("/hello"))
+        .header("x-id", "42")
+        .send()
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(response.text().await?, "even!");
+
+    let response = client
+        .get(server.url("/hello"))
+        .header("x-id", "7")
+        .send()
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_async_fn_matcher() -> Result<(), Error> {
+    // A token introspection stand-in
+    let tokens = Arc::new(RwLock::new(HashSet::from(["token-1".to_string()])));
+
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let mut mocks = MockSet::new();
+    let active_tokens = tokens.clone();
+    let matcher_calls = calls.clone();
+    mocks.mock(|when, then| {
+        when.get()
+            .path("/secret")
+            .matches_async_fn("active_token", move |req: Request| {
+                let tokens = active_tokens.clone();
+                matcher_calls.fetch_add(1, Ordering::Relaxed);
+                async move {
+                    let token = req
+                        .headers
+                        .get("authorization")
+                        .and_then(|value| value.strip_prefix("Bearer "))
+                        .map(String::from);
+                    match token {
+                        Some(token) => tokens.read().await.contains(&token),
+                        None => false,
+                    }
+                }
+            });
+        then.text("secret!");
+    });
+
+    let server = MockServer::new_http("async_fn_matcher").with_mocks(mocks);
+    server.start().await?;
+
+    let client = reqwest::Client::new();
+
+    let response = client
+        .get(server.url("/secret"))
+        .bearer_auth("token-1")
+        .send()
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(response.text().await?, "secret!");
+
+    // Revoke the token
+    tokens.write().await.clear();
+    let response = client
+        .get(server.url("/secret"))
+        .bearer_auth("token-1")
+        .send()
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+
+    // Async matchers are evaluated once per request
+    assert_eq!(calls.load(Ordering::Relaxed), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_matcher_check() {
+    let mock = Mock::new(|when, then| {
+        when.get()
+            .path("/hello")
+            .matches_fn("never", |_: &Request| Err::<(), _>("not today"));
+        then.ok();
+    });
+    let request = Request::new(Method::POST, "http://localhost/hello".parse().unwrap());
+    let reasons = futures::executor::block_on(mock.check(&request)).unwrap_err();
+    assert_eq!(reasons, ["method did not match", "not today"]);
+    assert_eq!(mock.match_count(), 0);
+}
+
+#[test]
+fn test_fn_matcher_order() {
+    let a: Arc<dyn Matcher> = Arc::new(from_fn("path", |_: &Request| true));
+    let b: Arc<dyn Matcher> = Arc::new(from_fn("path", |_: &Request| true));
+    let path: Arc<dyn Matcher> = Arc::new(path("/hello"));
+    assert!(*a == *a.clone());
+    assert_eq!(a.cmp(&a.clone()), std::cmp::Ordering::Equal);
+    // Function matchers with the same name are ordered, and never equal to other matchers
+    assert!(*a != *b);
+    assert_ne!(a.cmp(&b), std::cmp::Ordering::Equal);
+    assert_ne!(a.cmp(&path), std::cmp::Ordering::Equal);
+
+    let mut x = [a.clone(), path.clone(), b.clone()];
+    let mut y = [b, a, path];
+    x.sort();
+    y.sort();
+    assert!(x.iter().zip(&y).all(|(x, y)| **x == **y));
+}
@@ -0,0 +1,185 @@
+use anyhow::Error;
+use mocktail::{grpc::GrpcFrameDecoder, prelude::*};
+use mocktail_tests::pb::{
+    hello_client::HelloClient, HelloRequest, HelloResponse, HelloServerStreamingRequest,
+};
+use prost::Message;
+use test_log::test;
+use tonic::{codec::CompressionEncoding as TonicCompressionEncoding, transport::Channel};
+
+#[test(tokio::test)]
+async fn test_unary_compression() -> Result<(), Error> {
+    let encodings = [
+        (CompressionEncoding::Gzip, TonicCompressionEncoding::Gzip),
+        (
+            CompressionEncoding::Deflate,
+            TonicCompressionEncoding::Deflate,
+        ),
+        (CompressionEncoding::Zstd, TonicCompressionEncoding::Zstd),
+    ];
+
+    for (encoding, tonic_encoding) in encodings {
+        let mut mocks = MockSet::new();
+        mocks.mock(|when, then| {
+            when.path("/example.Hello/HelloUnary")
+                .pb(HelloRequest { name: "dan".into() });
+            then.pb(HelloResponse {
+                message: "hello dan!".into(),
+            })
+            .grpc_compression(encoding);
+        });
+
+        let server = MockServer::new_grpc("example.Hello").with_mocks(mocks);
+        server.start().await?;
+
+        let channel = Channel::from_shared(format!("http://0.0.0.0:{}", server.port().unwrap()))?
+            .connect()
+            .await?;
+        let mut client = HelloClient::new(channel)
+            .send_compressed(tonic_encoding)
+            .accept_compressed(tonic_encoding);
+
+        let response = client
+            .hello_unary(HelloRequest { name: "dan".into() })
+            .await?;
+        assert_eq!(
+            response.metadata().get("grpc-encoding").unwrap(),
+            encoding.as_str()
+        );
+        assert_eq!(
+            response.into_inner(),
+            HelloResponse {
+                message: "hello dan!".into(),
+            }
+        );
+    }
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_server_streaming_compression() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.path("/example.Hello/HelloServerStreaming")
+            .pb(HelloServerStreamingRequest {
+                names: vec!["dan".into(), "gaurav".into()],
+            });
+        then.pb_stream([
+            HelloResponse {
+                message: "hello dan!".into(),
+            },
+            HelloResponse {
+                message: "hello gaurav!".into(),
+            },
+        ])
+        .grpc_compression(CompressionEncoding::Gzip);
+    });
+
+    let server = MockServer::new_grpc("example.Hello").with_mocks(mocks);
+    server.start().await?;
+
+    let channel = Channel::from_shared(format!("http://0.0.0.0:{}", server.port().unwrap()))?
+        .connect()
+        .await?;
+
+    // Client accepts gzip
+    let mut client = HelloClient::new(channel.clone())
+        .send_compressed(TonicCompressionEncoding::Gzip)
+        .accept_compressed(TonicCompressionEncoding::Gzip);
+    let response = client
+        .hello_server_streaming(HelloServerStreamingRequest {
+            names: vec!["dan".into(), "gaurav".into()],
+        })
+        .await?;
+    assert_eq!(response.metadata().get("grpc-encoding").unwrap(), "gzip");
+    let mut stream = response.into_inner();
+    let mut messages = Vec::new();
+    while let Some(message) = stream.message().await? {
+        messages.push(message.message);
+    }
+    assert_eq!(messages, ["hello dan!", "hello gaurav!"]);
+
+    // Client does not accept compression, messages are sent uncompressed
+    let mut client = HelloClient::new(channel);
+    let response = client
+        .hello_server_streaming(HelloServerStreamingRequest {
+            names: vec!["dan".into(), "gaurav".into()],
+        })
+        .await?;
+    assert!(response.metadata().get("grpc-encoding").is_none());
+    let mut stream = response.into_inner();
+    let mut messages = Vec::new();
+    while let Some(message) = stream.message().await? {
+        messages.push(message.message);
+    }
+    assert_eq!(messages, ["hello dan!", "hello gaurav!"]);
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_compression_split_message() -> Result<(), Error> {
+    // Length-prefixed message split across two response body chunks
+    let message = HelloResponse {
+        message: "hello dan!".into(),
+    };
+    let frame = mocktail::grpc::codec::encode_frame(false, &message.encode_to_vec());
+    let (first, second) = frame.split_at(3);
+
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.path("/example.Hello/HelloUnary")
+            .pb(HelloRequest { name: "dan".into() });
+        then.bytes_stream([first.to_vec(), second.to_vec()])
+            .grpc_compression(CompressionEncoding::Gzip);
+    });
+
+    let server = MockServer::new_grpc("example.Hello").with_mocks(mocks);
+    server.start().await?;
+
+    let client = reqwest::Client::builder().http2_prior_knowledge().build()?;
+    let request = HelloRequest { name: "dan".into() };
+    let response = client
+        .post(server.url("/example.Hello/HelloUnary"))
+        .header("content-type", "application/grpc")
+        .header("grpc-accept-encoding", "gzip")
+        .body(mocktail::grpc::codec::encode_frame(
+            false,
+            &request.encode_to_vec(),
+        ))
+        .send()
+        .await?;
+    assert_eq!(response.headers().get("grpc-encoding").unwrap(), "gzip");
+
+    // The message is sent as a single compressed message
+    let body = response.bytes().await?;
+    let mut decoder = GrpcFrameDecoder::new();
+    let frames = decoder.decode(&body);
+    assert!(!decoder.has_remaining());
+    assert_eq!(frames.len(), 1);
+    assert!(frames[0].compressed);
+    let data = CompressionEncoding::Gzip.decompress(&frames[0].data)?;
+    assert_eq!(HelloResponse::decode(&data[..])?, message);
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_unsupported_encoding() -> Result<(), Error> {
+    let server = MockServer::new_grpc("example.Hello");
+    server.start().await?;
+
+    let client = reqwest::Client::builder().http2_prior_knowledge().build()?;
+    let response = client
+        .post(server.url("/example.Hello/HelloUnary"))
+        .header("content-type", "application/grpc")
+        .header("grpc-encoding", "snappy")
+        .send()
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(response.headers().get("grpc-status").unwrap(), "12");
+    assert!(response.headers().get("grpc-accept-encoding").is_some());
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use anyhow::Error;
+use mocktail::prelude::*;
+use test_log::test;
+use tonic::transport::Channel;
+use tonic_health::pb::{
+    health_check_response::ServingStatus as PbServingStatus, health_client::HealthClient,
+    HealthCheckRequest,
+};
+
+#[test(tokio::test)]
+async fn test_health_check() -> Result<(), Error> {
+    let server = MockServer::new_grpc("example.Hello").with_health();
+    server.start().await?;
+    server.set_health("example.Hello", ServingStatus::NotServing);
+
+    let channel = Channel::from_shared(format!("http://0.0.0.0:{}", server.port().unwrap()))?
+        .connect()
+        .await?;
+    let mut client = HealthClient::new(channel);
+
+    // Overall server status
+    let response = client
+        .check(HealthCheckRequest { service: "".into() })
+        .await?;
+    assert_eq!(response.into_inner().status(), PbServingStatus::Serving);
+
+    let response = client
+        .check(HealthCheckRequest {
+            service: "example.Hello".into(),
+        })
+        .await?;
+    assert_eq!(response.into_inner().status(), PbServingStatus::NotServing);
+
+    server.set_health("example.Hello", ServingStatus::Serving);
+    let response = client
+        .check(HealthCheckRequest {
+            service: "example.Hello".into(),
+        })
+        .await?;
+    assert_eq!(response.into_inner().status(), PbServingStatus::Serving);
+
+    // Unknown service
+    let result = client
+        .check(HealthCheckRequest {
+            service: "example.Unknown".into(),
+        })
+        .await;
+    assert!(result.is_err_and(|e| e.code() == tonic::Code::NotFound));
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_health_watch() -> Result<(), Error> {
+    let server = MockServer::new_grpc("example.Hello").with_health();
+    server.start().await?;
+
+    let channel = Channel::from_shared(format!("http://0.0.0.0:{}", server.port().unwrap()))?
+        .connect()
+        .await?;
+    let mut client = HealthClient::new(channel);
+
+    let response = client
+        .watch(HealthCheckRequest {
+            service: "example.Hello".into(),
+        })
+        .await?;
+    let mut stream = response.into_inner();
+
+    let message = stream.message().await?.unwrap();
+    assert_eq!(message.status(), PbServingStatus::ServiceUnknown);
+
+    server.set_health("example.Hello", ServingStatus::Serving);
+    let message = stream.message().await?.unwrap();
+    assert_eq!(message.status(), PbServingStatus::Serving);
+
+    // Setting the same status again should not push an update
+    server.set_health("example.Hello", ServingStatus::Serving);
+    server.set_health("example.Hello", ServingStatus::NotServing);
+    let message = stream.message().await?.unwrap();
+    assert_eq!(message.status(), PbServingStatus::NotServing);
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_health_disabled() -> Result<(), Error> {
+    let server = MockServer::new_grpc("example.Hello");
+    server.start().await?;
+
+    let channel = Channel::from_shared(format!("http://0.0.0.0:{}", server.port().unwrap()))?
+        .connect()
+        .await?;
+    let mut client = HealthClient::new(channel);
+
+    // Health requests are matched against mocks
+    let result = client
+        .check(HealthCheckRequest { service: "".into() })
+        .await;
+    assert!(result.is_err_and(|e| e.code() == tonic::Code::NotFound));
+
+    Ok(())
+}
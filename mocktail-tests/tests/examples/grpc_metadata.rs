@@ -0,0 +1,110 @@
+use anyhow::Error;
+use mocktail::prelude::*;
+use mocktail_tests::pb::{
+    hello_client::HelloClient, HelloRequest, HelloResponse, HelloServerStreamingRequest,
+};
+use test_log::test;
+use tonic::transport::Channel;
+
+#[test(tokio::test)]
+async fn test_unary_metadata() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.path("/example.Hello/HelloUnary")
+            .pb(HelloRequest { name: "dan".into() });
+        then.pb(HelloResponse {
+            message: "hello dan!".into(),
+        })
+        .grpc_metadata([("x-request-id", "abc123")])
+        .grpc_metadata_bin([("x-token", [0u8, 1, 2, 255])]);
+    });
+    mocks.mock(|when, then| {
+        when.path("/example.Hello/HelloUnary").pb(HelloRequest {
+            name: "expired".into(),
+        });
+        then.unauthorized()
+            .message("token expired")
+            .grpc_trailers([("x-auth-error", "expired")])
+            .grpc_trailers_bin([("x-challenge-bin", b"refresh")]);
+    });
+
+    let server = MockServer::new_grpc("example.Hello").with_mocks(mocks);
+    server.start().await?;
+
+    let channel = Channel::from_shared(format!("http://0.0.0.0:{}", server.port().unwrap()))?
+        .connect()
+        .await?;
+    let mut client = HelloClient::new(channel);
+
+    // Initial metadata
+    let response = client
+        .hello_unary(HelloRequest { name: "dan".into() })
+        .await?;
+    let metadata = response.metadata();
+    assert_eq!(metadata.get("x-request-id").unwrap(), "abc123");
+    assert_eq!(
+        metadata.get_bin("x-token-bin").unwrap().to_bytes()?,
+        &[0u8, 1, 2, 255][..]
+    );
+
+    // Trailing metadata
+    let status = client
+        .hello_unary(HelloRequest {
+            name: "expired".into(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::Unauthenticated);
+    assert_eq!(status.metadata().get("x-auth-error").unwrap(), "expired");
+    assert_eq!(
+        status
+            .metadata()
+            .get_bin("x-challenge-bin")
+            .unwrap()
+            .to_bytes()?,
+        &b"refresh"[..]
+    );
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_server_streaming_metadata() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.path("/example.Hello/HelloServerStreaming")
+            .pb(HelloServerStreamingRequest {
+                names: vec!["dan".into()],
+            });
+        then.pb_stream([HelloResponse {
+            message: "hello dan!".into(),
+        }])
+        .grpc_metadata([("x-request-id", "abc123")])
+        .grpc_trailers([("x-cursor", "next")]);
+    });
+
+    let server = MockServer::new_grpc("example.Hello").with_mocks(mocks);
+    server.start().await?;
+
+    let channel = Channel::from_shared(format!("http://0.0.0.0:{}", server.port().unwrap()))?
+        .connect()
+        .await?;
+    let mut client = HelloClient::new(channel);
+
+    let response = client
+        .hello_server_streaming(HelloServerStreamingRequest {
+            names: vec!["dan".into()],
+        })
+        .await?;
+    assert_eq!(response.metadata().get("x-request-id").unwrap(), "abc123");
+    assert!(response.metadata().get("x-cursor").is_none());
+
+    let mut stream = response.into_inner();
+    let message = stream.message().await?.unwrap();
+    assert_eq!(message.message, "hello dan!");
+    assert!(stream.message().await?.is_none());
+    let trailers = stream.trailers().await?.unwrap();
+    assert_eq!(trailers.get("x-cursor").unwrap(), "next");
+
+    Ok(())
+}
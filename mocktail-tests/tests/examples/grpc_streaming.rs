@@ -1,10 +1,14 @@
 use anyhow::Error;
 use futures::StreamExt;
-use mocktail::prelude::*;
+use mocktail::{
+    grpc::{GrpcFrame, GrpcFrameDecoder},
+    prelude::*,
+};
 use mocktail_tests::pb::{
     hello_client::HelloClient, HelloClientStreamingResponse, HelloRequest, HelloResponse,
     HelloServerStreamingRequest,
 };
+use prost::Message;
 use test_log::test;
 use tonic::transport::Channel;
 use tracing::debug;
@@ -94,3 +98,328 @@ async fn test_server_streaming() -> Result<(), Error> {
 
     Ok(())
 }
+
+#[test(tokio::test)]
+async fn test_bidi_streaming() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    for name in ["dan", "mateus", "paulo"] {
+        mocks.mock(|when, then| {
+            when.path("/example.Hello/HelloBidiStreaming")
+                .bidi()
+                .pb(HelloRequest { name: name.into() });
+            then.pb(HelloResponse {
+                message: format!("hello {name}!"),
+            });
+        });
+    }
+
+    let server = MockServer::new_grpc("example.Hello").with_mocks(mocks);
+    server.start().await?;
+
+    let channel = Channel::from_shared(format!("http://0.0.0.0:{}", server.port().unwrap()))?
+        .connect()
+        .await?;
+    let mut client = HelloClient::new(channel);
+
+    // Each response is received before the next request message is sent
+    let (request_tx, request_rx) = tokio::sync::mpsc::channel(1);
+    request_tx.send(HelloRequest { name: "dan".into() }).await?;
+    let response = client
+        .hello_bidi_streaming(tokio_stream::wrappers::ReceiverStream::new(request_rx))
+        .await?;
+    let mut stream = response.into_inner();
+
+    let message = stream.message().await?.unwrap();
+    assert_eq!(message.message, "hello dan!");
+
+    request_tx
+        .send(HelloRequest {
+            name: "mateus".into(),
+        })
+        .await?;
+    let message = stream.message().await?.unwrap();
+    assert_eq!(message.message, "hello mateus!");
+
+    request_tx
+        .send(HelloRequest {
+            name: "paulo".into(),
+        })
+        .await?;
+    let message = stream.message().await?.unwrap();
+    assert_eq!(message.message, "hello paulo!");
+
+    // Closing the request stream ends the call
+    drop(request_tx);
+    assert!(stream.message().await?.is_none());
+
+    // An unmatched message ends the call
+    let response = client
+        .hello_bidi_streaming(futures::stream::iter([
+            HelloRequest { name: "dan".into() },
+            HelloRequest { name: "ian".into() },
+        ]))
+        .await?;
+    let mut stream = response.into_inner();
+    let message = stream.message().await?.unwrap();
+    assert_eq!(message.message, "hello dan!");
+    let status = stream.message().await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::NotFound);
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_bidi_streaming_without_path() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.bidi().pb(HelloRequest { name: "dan".into() });
+        then.pb(HelloResponse {
+            message: "hello dan!".into(),
+        });
+    });
+    mocks.mock(|when, then| {
+        when.path("/example.Hello/HelloUnary").pb(HelloRequest {
+            name: "mateus".into(),
+        });
+        then.pb(HelloResponse {
+            message: "hello mateus!".into(),
+        });
+    });
+
+    let server = MockServer::new_grpc("example.Hello").with_mocks(mocks);
+    server.start().await?;
+
+    let channel = Channel::from_shared(format!("http://0.0.0.0:{}", server.port().unwrap()))?
+        .connect()
+        .await?;
+    let mut client = HelloClient::new(channel);
+
+    // A bidi mock without a path does not switch other calls to per-message matching
+    let response = client
+        .hello_unary(HelloRequest {
+            name: "mateus".into(),
+        })
+        .await?;
+    assert_eq!(response.into_inner().message, "hello mateus!");
+
+    let response = client
+        .hello_bidi_streaming(futures::stream::iter([HelloRequest { name: "dan".into() }]))
+        .await?;
+    let mut stream = response.into_inner();
+    let message = stream.message().await?.unwrap();
+    assert_eq!(message.message, "hello dan!");
+    assert!(stream.message().await?.is_none());
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_bidi_streaming_batched() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    for name in ["dan", "mateus"] {
+        mocks.mock(|when, then| {
+            when.path("/example.Hello/HelloBidiStreaming")
+                .bidi()
+                .pb(HelloRequest { name: name.into() });
+            then.pb_stream([
+                HelloResponse {
+                    message: format!("hello {name}!"),
+                },
+                HelloResponse {
+                    message: format!("bye {name}!"),
+                },
+            ]);
+        });
+    }
+
+    let server = MockServer::new_grpc("example.Hello").with_mocks(mocks);
+    server.start().await?;
+
+    let channel = Channel::from_shared(format!("http://0.0.0.0:{}", server.port().unwrap()))?
+        .connect()
+        .await?;
+    let mut client = HelloClient::new(channel);
+
+    // Messages sent together are still matched individually
+    let response = client
+        .hello_bidi_streaming(futures::stream::iter([
+            HelloRequest { name: "dan".into() },
+            HelloRequest {
+                name: "mateus".into(),
+            },
+        ]))
+        .await?;
+    let mut stream = response.into_inner();
+
+    let mut messages = Vec::new();
+    while let Some(message) = stream.message().await? {
+        messages.push(message.message);
+    }
+    assert_eq!(
+        messages,
+        ["hello dan!", "bye dan!", "hello mateus!", "bye mateus!"]
+    );
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_bidi_streaming_message_index() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.path("/example.Hello/HelloBidiStreaming")
+            .bidi()
+            .pb(HelloRequest { name: "dan".into() });
+        then.pb(HelloResponse {
+            message: "hello dan!".into(),
+        });
+    });
+    mocks.mock(|when, then| {
+        when.path("/example.Hello/HelloBidiStreaming")
+            .bidi()
+            .pb_message(
+                1,
+                HelloRequest {
+                    name: "mateus".into(),
+                },
+            );
+        then.pb(HelloResponse {
+            message: "mateus is second!".into(),
+        });
+    });
+
+    let server = MockServer::new_grpc("example.Hello").with_mocks(mocks);
+    server.start().await?;
+
+    let channel = Channel::from_shared(format!("http://0.0.0.0:{}", server.port().unwrap()))?
+        .connect()
+        .await?;
+    let mut client = HelloClient::new(channel);
+
+    // Message indices count from the start of the stream, not from the last match
+    let response = client
+        .hello_bidi_streaming(futures::stream::iter([
+            HelloRequest { name: "dan".into() },
+            HelloRequest {
+                name: "mateus".into(),
+            },
+        ]))
+        .await?;
+    let mut stream = response.into_inner();
+    let mut messages = Vec::new();
+    while let Some(message) = stream.message().await? {
+        messages.push(message.message);
+    }
+    assert_eq!(messages, ["hello dan!", "mateus is second!"]);
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_client_streaming_whole_stream() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.path("/example.Hello/HelloClientStreaming");
+        then.pb(HelloClientStreamingResponse {
+            messages: vec!["hello everyone!".into()],
+        });
+    });
+
+    let server = MockServer::new_grpc("example.Hello").with_mocks(mocks);
+    server.start().await?;
+
+    let channel = Channel::from_shared(format!("http://0.0.0.0:{}", server.port().unwrap()))?
+        .connect()
+        .await?;
+    let mut client = HelloClient::new(channel);
+
+    // Mocks without bidi are matched once, by the whole request stream
+    let response = client
+        .hello_client_streaming(futures::stream::iter([
+            HelloRequest { name: "dan".into() },
+            HelloRequest {
+                name: "mateus".into(),
+            },
+            HelloRequest {
+                name: "paulo".into(),
+            },
+        ]))
+        .await?;
+    assert_eq!(response.into_inner().messages, ["hello everyone!"]);
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_client_streaming_split_frames() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.path("/example.Hello/HelloClientStreaming")
+            .pb_messages([
+                HelloRequest {
+                    name: "mateus".into(),
+                },
+                HelloRequest {
+                    name: "paulo".into(),
+                },
+            ]);
+        then.pb(HelloClientStreamingResponse {
+            messages: vec!["hello mateus!".into(), "hello paulo!".into()],
+        });
+    });
+    mocks.mock(|when, then| {
+        when.path("/example.Hello/HelloClientStreaming")
+            .pb_message(0, HelloRequest { name: "dan".into() });
+        then.pb(HelloClientStreamingResponse {
+            messages: vec!["hello dan!".into()],
+        });
+    });
+
+    let server = MockServer::new_grpc("example.Hello").with_mocks(mocks);
+    server.start().await?;
+
+    let client = reqwest::Client::builder().http2_prior_knowledge().build()?;
+
+    // Send length-prefixed messages split across arbitrary chunks
+    let mut body = Vec::new();
+    for name in ["mateus", "paulo"] {
+        let message = HelloRequest { name: name.into() }.encode_to_vec();
+        body.extend(
+            GrpcFrame {
+                compressed: false,
+                data: message.into(),
+            }
+            .encode(),
+        );
+    }
+    let chunks = body
+        .chunks(3)
+        .map(|chunk| Ok::<_, std::io::Error>(chunk.to_vec()))
+        .collect::<Vec<_>>();
+    let response = client
+        .post(server.url("/example.Hello/HelloClientStreaming"))
+        .header("content-type", "application/grpc")
+        .header("te", "trailers")
+        .body(reqwest::Body::wrap_stream(futures::stream::iter(chunks)))
+        .send()
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::OK);
+
+    let mut decoder = GrpcFrameDecoder::new();
+    let frames = decoder.decode(response.bytes().await?);
+    assert_eq!(frames.len(), 1);
+    let message = HelloClientStreamingResponse::decode(frames[0].data.clone())?;
+    assert_eq!(message.messages, ["hello mateus!", "hello paulo!"]);
+
+    // Match the first message only
+    let channel = Channel::from_shared(format!("http://0.0.0.0:{}", server.port().unwrap()))?
+        .connect()
+        .await?;
+    let mut client = HelloClient::new(channel);
+    let response = client
+        .hello_client_streaming(futures::stream::iter([HelloRequest { name: "dan".into() }]))
+        .await?;
+    assert_eq!(response.into_inner().messages, ["hello dan!"]);
+
+    Ok(())
+}
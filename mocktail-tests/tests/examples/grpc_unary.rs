@@ -1,8 +1,11 @@
+use std::time::Duration;
+
 use anyhow::Error;
 use mocktail::prelude::*;
 use mocktail_tests::pb::{hello_client::HelloClient, HelloRequest, HelloResponse};
 use test_log::test;
 use tonic::transport::Channel;
+use tonic_types::StatusExt;
 
 #[test(tokio::test)]
 async fn test_unary() -> Result<(), Error> {
@@ -77,3 +80,184 @@ async fn test_unary_errors() -> Result<(), anyhow::Error> {
 
     Ok(())
 }
+
+#[test(tokio::test)]
+async fn test_unary_error_details() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.path("/example.Hello/HelloUnary")
+            .pb(HelloRequest { name: "".into() });
+        then.bad_request()
+            .message("invalid request")
+            .grpc_error_details([ErrorDetail::bad_request([("name", "must not be empty")])]);
+    });
+    mocks.mock(|when, then| {
+        when.path("/example.Hello/HelloUnary")
+            .pb(HelloRequest { name: "dan".into() });
+        then.service_unavailable()
+            .message("try again later")
+            .grpc_error_details([
+                ErrorDetail::retry_info(Duration::from_secs(2)),
+                ErrorDetail::error_info("OVERLOADED", "example.com", [("region", "us-east")]),
+            ]);
+    });
+
+    let server = MockServer::new_grpc("example.Hello").with_mocks(mocks);
+    server.start().await?;
+
+    let channel = Channel::from_shared(format!("http://0.0.0.0:{}", server.port().unwrap()))?
+        .connect()
+        .await?;
+    let mut client = HelloClient::new(channel);
+
+    let status = client
+        .hello_unary(HelloRequest { name: "".into() })
+        .await
+        .unwrap_err();
+    assert_eq!(status.message(), "invalid request");
+    let bad_request = status.get_details_bad_request().unwrap();
+    assert_eq!(bad_request.field_violations.len(), 1);
+    assert_eq!(bad_request.field_violations[0].field, "name");
+    assert_eq!(
+        bad_request.field_violations[0].description,
+        "must not be empty"
+    );
+
+    let status = client
+        .hello_unary(HelloRequest { name: "dan".into() })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::Unavailable);
+    let retry_info = status.get_details_retry_info().unwrap();
+    assert_eq!(retry_info.retry_delay, Some(Duration::from_secs(2)));
+    let error_info = status.get_details_error_info().unwrap();
+    assert_eq!(error_info.reason, "OVERLOADED");
+    assert_eq!(error_info.domain, "example.com");
+    assert_eq!(
+        error_info.metadata.get("region").map(String::as_str),
+        Some("us-east")
+    );
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_unary_grpc_status() -> Result<(), Error> {
+    let codes = [
+        (
+            "already_exists",
+            Code::AlreadyExists,
+            tonic::Code::AlreadyExists,
+        ),
+        ("aborted", Code::Aborted, tonic::Code::Aborted),
+        (
+            "failed_precondition",
+            Code::FailedPrecondition,
+            tonic::Code::FailedPrecondition,
+        ),
+        (
+            "resource_exhausted",
+            Code::ResourceExhausted,
+            tonic::Code::ResourceExhausted,
+        ),
+        ("data_loss", Code::DataLoss, tonic::Code::DataLoss),
+        ("cancelled", Code::Cancelled, tonic::Code::Cancelled),
+        ("out_of_range", Code::OutOfRange, tonic::Code::OutOfRange),
+    ];
+
+    let mut mocks = MockSet::new();
+    for (name, code, _) in codes {
+        mocks.mock(|when, then| {
+            when.path("/example.Hello/HelloUnary")
+                .pb(HelloRequest { name: name.into() });
+            // The explicit gRPC status takes precedence over the HTTP status
+            then.internal_server_error().grpc_status(code).message(name);
+        });
+    }
+
+    let server = MockServer::new_grpc("example.Hello").with_mocks(mocks);
+    server.start().await?;
+
+    let channel = Channel::from_shared(format!("http://0.0.0.0:{}", server.port().unwrap()))?
+        .connect()
+        .await?;
+    let mut client = HelloClient::new(channel);
+
+    for (name, _, expected) in codes {
+        let status = client
+            .hello_unary(HelloRequest { name: name.into() })
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), expected);
+        assert_eq!(status.message(), name);
+    }
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_unary_deadline() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.path("/example.Hello/HelloUnary").pb(HelloRequest {
+            name: "slow".into(),
+        });
+        then.pb(HelloResponse {
+            message: "hello slow!".into(),
+        })
+        .delay(Duration::from_millis(500));
+    });
+    mocks.mock(|when, then| {
+        when.path("/example.Hello/HelloUnary")
+            .pb(HelloRequest { name: "dan".into() })
+            .grpc_timeout_at_most(Duration::from_secs(1));
+        then.pb(HelloResponse {
+            message: "hello dan!".into(),
+        });
+    });
+
+    let server = MockServer::new_grpc("example.Hello").with_mocks(mocks);
+    server.start().await?;
+
+    let channel = Channel::from_shared(format!("http://0.0.0.0:{}", server.port().unwrap()))?
+        .connect()
+        .await?;
+    let mut client = HelloClient::new(channel);
+
+    // Delay exceeds deadline
+    let mut request = tonic::Request::new(HelloRequest {
+        name: "slow".into(),
+    });
+    request.set_timeout(Duration::from_millis(50));
+    let status = client.hello_unary(request).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::DeadlineExceeded);
+
+    // Delay within deadline
+    let mut request = tonic::Request::new(HelloRequest {
+        name: "slow".into(),
+    });
+    request.set_timeout(Duration::from_secs(5));
+    let response = client.hello_unary(request).await?;
+    assert_eq!(response.into_inner().message, "hello slow!");
+
+    // Deadline propagated
+    let mut request = tonic::Request::new(HelloRequest { name: "dan".into() });
+    request.set_timeout(Duration::from_millis(200));
+    let response = client.hello_unary(request).await?;
+    assert_eq!(response.into_inner().message, "hello dan!");
+
+    // Deadline not propagated
+    let status = client
+        .hello_unary(HelloRequest { name: "dan".into() })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::NotFound);
+
+    // Deadline too long
+    let mut request = tonic::Request::new(HelloRequest { name: "dan".into() });
+    request.set_timeout(Duration::from_secs(10));
+    let status = client.hello_unary(request).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::NotFound);
+
+    Ok(())
+}
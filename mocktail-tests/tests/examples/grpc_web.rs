@@ -0,0 +1,108 @@
+use anyhow::Error;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use mocktail::{
+    grpc::{GrpcFrame, GrpcFrameDecoder},
+    prelude::*,
+};
+use mocktail_tests::pb::{hello_client::HelloClient, HelloRequest, HelloResponse};
+use prost::Message;
+use test_log::test;
+use tonic_web::GrpcWebClientLayer;
+
+#[test(tokio::test)]
+async fn test_grpc_web() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.path("/example.Hello/HelloUnary")
+            .pb(HelloRequest { name: "dan".into() });
+        then.pb(HelloResponse {
+            message: "hello dan!".into(),
+        })
+        .grpc_trailers([("x-trailer", "done")]);
+    });
+    mocks.mock(|when, then| {
+        when.path("/example.Hello/HelloUnary").pb(HelloRequest {
+            name: "unexpected_error".into(),
+        });
+        then.internal_server_error().message("unexpected error");
+    });
+
+    let server = MockServer::new_grpc("example.Hello").with_mocks(mocks);
+    server.start().await?;
+
+    // gRPC-Web over HTTP/1.1
+    let client = Client::builder(TokioExecutor::new()).build_http();
+    let service = tower::ServiceBuilder::new()
+        .layer(GrpcWebClientLayer::new())
+        .service(client);
+    let mut client =
+        HelloClient::with_origin(service, server.base_url().unwrap().as_str().parse()?);
+
+    let response = client
+        .hello_unary(HelloRequest { name: "dan".into() })
+        .await?;
+    assert_eq!(response.into_inner().message, "hello dan!");
+
+    let status = client
+        .hello_unary(HelloRequest {
+            name: "unexpected_error".into(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::Internal);
+    assert_eq!(status.message(), "unexpected error");
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_grpc_web_text() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.path("/example.Hello/HelloUnary")
+            .pb(HelloRequest { name: "dan".into() });
+        then.pb(HelloResponse {
+            message: "hello dan!".into(),
+        });
+    });
+
+    let server = MockServer::new_grpc("example.Hello").with_mocks(mocks);
+    server.start().await?;
+
+    let message = HelloRequest { name: "dan".into() }.encode_to_vec();
+    let body = STANDARD.encode(
+        GrpcFrame {
+            compressed: false,
+            data: message.into(),
+        }
+        .encode(),
+    );
+    let client = reqwest::Client::builder().http1_only().build()?;
+    let response = client
+        .post(server.url("/example.Hello/HelloUnary"))
+        .header("content-type", "application/grpc-web-text")
+        .body(body)
+        .send()
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "application/grpc-web-text+proto"
+    );
+
+    // Response may be a concatenation of padded base64 segments
+    let body = response.bytes().await?;
+    let mut decoder = GrpcFrameDecoder::new();
+    let mut frames = Vec::new();
+    for quantum in body.chunks(4) {
+        frames.extend(decoder.decode(STANDARD.decode(quantum)?));
+    }
+    assert_eq!(frames.len(), 2);
+    let message = HelloResponse::decode(frames[0].data.clone())?;
+    assert_eq!(message.message, "hello dan!");
+    // Trailers frame
+    assert_eq!(frames[1].data, "grpc-status: 0\r\n");
+
+    Ok(())
+}
@@ -1,6 +1,9 @@
 use anyhow::Error;
 use eventsource_stream::{Event, Eventsource};
 use futures::{stream, Stream, StreamExt};
+use http_body_util::{BodyExt, StreamBody};
+use hyper::body::{Bytes, Frame};
+use hyper_util::rt::{TokioExecutor, TokioIo};
 use mocktail::prelude::*;
 use serde::{Deserialize, Serialize};
 use test_log::test;
@@ -54,6 +57,7 @@ async fn test_json_lines_stream() -> Result<(), Error> {
         .await?;
     dbg!(&response);
     assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/x-ndjson");
 
     let mut responses = Vec::with_capacity(2);
     let mut stream = response.bytes_stream();
@@ -154,3 +158,188 @@ fn json_lines_stream(
         .collect::<Vec<Result<_, std::io::Error>>>();
     stream::iter(chunks)
 }
+
+#[test(tokio::test)]
+async fn test_sse() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.get().path("/sse");
+        then.sse([
+            SseEvent::new("msg1").with_id("1"),
+            SseEvent::json(HelloResponse {
+                message: "hello dan!".into(),
+            })
+            .with_event("hello")
+            .with_id("2"),
+            SseEvent::new("internal error").with_event("error"),
+        ]);
+    });
+    mocks.mock(|when, then| {
+        when.post()
+            .path("/sse")
+            .sse([SseEvent::new("ping").with_event("ping")]);
+        then.sse_json([HelloResponse {
+            message: "pong".into(),
+        }]);
+    });
+    mocks.mock(|when, then| {
+        when.get().path("/sse/headers");
+        then.sse(["msg1"]).headers([("x-request-id", "1")]);
+    });
+    mocks.mock(|when, then| {
+        when.get().path("/sse/content-type");
+        then.headers([("content-type", "text/event-stream; charset=utf-8")])
+            .sse(["msg1"]);
+    });
+
+    let server = MockServer::new_http("sse").with_mocks(mocks);
+    server.start().await?;
+
+    let client = reqwest::Client::new();
+
+    let response = client.get(server.url("/sse")).send().await?;
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "text/event-stream"
+    );
+    let events = response
+        .bytes_stream()
+        .eventsource()
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[0].data, "msg1");
+    assert_eq!(events[0].id, "1");
+    assert_eq!(events[1].event, "hello");
+    let message: HelloResponse = serde_json::from_str(&events[1].data)?;
+    assert_eq!(message.message, "hello dan!");
+    assert_eq!(events[2].event, "error");
+
+    // Match SSE request body
+    let response = client
+        .post(server.url("/sse"))
+        .body("event: ping\r\ndata: ping\r\n\r\n")
+        .send()
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::OK);
+    let events = mocktail::sse::parse(&response.bytes().await?);
+    assert_eq!(
+        events,
+        [SseEvent::json(HelloResponse {
+            message: "pong".into(),
+        })]
+    );
+
+    // SSE headers are kept when headers are set after the body
+    let response = client.get(server.url("/sse/headers")).send().await?;
+    assert_eq!(response.headers()["content-type"], "text/event-stream");
+    assert_eq!(response.headers()["cache-control"], "no-cache");
+    assert_eq!(response.headers()["x-request-id"], "1");
+
+    // A content type set by the mock is not replaced
+    let response = client.get(server.url("/sse/content-type")).send().await?;
+    assert_eq!(
+        response.headers()["content-type"],
+        "text/event-stream; charset=utf-8"
+    );
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_sse_keep_alive() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.get().path("/sse");
+        then.sse(["msg1"])
+            .sse_keep_alive(std::time::Duration::from_millis(10));
+    });
+
+    let server = MockServer::new_http("sse").with_mocks(mocks);
+    server.start().await?;
+
+    let client = reqwest::Client::new();
+    let response = client.get(server.url("/sse")).send().await?;
+    let mut stream = response.bytes_stream();
+    let mut chunks = Vec::new();
+    while chunks.len() < 3 {
+        let chunk = tokio::time::timeout(std::time::Duration::from_secs(1), stream.next())
+            .await?
+            .unwrap()?;
+        chunks.push(chunk);
+    }
+    assert_eq!(chunks[0], "data: msg1\n\n");
+    assert_eq!(chunks[1], ": keep-alive\n\n");
+    assert_eq!(chunks[2], ": keep-alive\n\n");
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_streaming_status_and_trailers() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.post().path("/hello").bytes_stream(["dan", "mateus"]);
+        then.bytes_stream(["hello dan!", "hello mateus!"])
+            .status(StatusCode::CREATED)
+            .headers([("x-request-id", "1")])
+            .trailers([("x-checksum", "abc")])
+            .trailers([("x-count", "2")]);
+    });
+
+    let server = MockServer::new_http("hello").with_mocks(mocks);
+    server.start().await?;
+
+    let stream = tokio::net::TcpStream::connect(("127.0.0.1", server.port().unwrap())).await?;
+    let (mut sender, conn) =
+        hyper::client::conn::http2::handshake(TokioExecutor::new(), TokioIo::new(stream)).await?;
+    tokio::spawn(conn);
+
+    let chunks = ["dan", "mateus"]
+        .into_iter()
+        .map(|chunk| Ok::<_, std::io::Error>(Frame::data(Bytes::from(chunk))));
+    let request = http::Request::builder()
+        .method(http::Method::POST)
+        .uri(server.url("/hello").as_str())
+        .body(StreamBody::new(stream::iter(chunks)))?;
+    let response = sender.send_request(request).await?;
+    assert_eq!(response.status(), http::StatusCode::CREATED);
+    assert_eq!(response.headers()["x-request-id"], "1");
+
+    let collected = response.into_body().collect().await?;
+    assert_eq!(
+        collected.trailers().map(|trailers| &trailers["x-checksum"]),
+        Some(&http::HeaderValue::from_static("abc"))
+    );
+    assert_eq!(
+        collected.trailers().map(|trailers| &trailers["x-count"]),
+        Some(&http::HeaderValue::from_static("2"))
+    );
+    assert_eq!(collected.to_bytes(), "hello dan!hello mateus!");
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_streaming_not_found() -> Result<(), Error> {
+    let server = MockServer::new_http("hello");
+    server.start().await?;
+
+    let client = reqwest::Client::builder().http2_prior_knowledge().build()?;
+    let chunks = ["dan", "mateus"]
+        .into_iter()
+        .map(Ok)
+        .collect::<Vec<Result<_, std::io::Error>>>();
+    let response = client
+        .post(server.url("/hello"))
+        .body(reqwest::Body::wrap_stream(stream::iter(chunks)))
+        .send()
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+    assert_eq!(response.text().await?, "mock not found");
+
+    Ok(())
+}
@@ -184,3 +184,45 @@ async fn test_unary_headers() -> Result<(), Error> {
 
     Ok(())
 }
+
+#[test(tokio::test)]
+async fn test_methods() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.patch().path("/hello");
+        then.text("patched");
+    });
+    mocks.mock(|when, then| {
+        when.options().path("/hello");
+        then.headers([("allow", "GET, PATCH, OPTIONS, PURGE")]);
+    });
+    mocks.mock(|when, then| {
+        when.method(Method::Other("PURGE".into())).path("/hello");
+        then.text("purged");
+    });
+
+    let server = MockServer::new_http("methods").with_mocks(mocks);
+    server.start().await?;
+
+    let client = reqwest::Client::builder().http2_prior_knowledge().build()?;
+
+    let response = client.patch(server.url("/hello")).send().await?;
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(response.text().await?, "patched");
+
+    let response = client
+        .request(http::Method::OPTIONS, server.url("/hello"))
+        .send()
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert!(response.headers().contains_key("allow"));
+
+    let response = client
+        .request(http::Method::from_bytes(b"PURGE")?, server.url("/hello"))
+        .send()
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(response.text().await?, "purged");
+
+    Ok(())
+}
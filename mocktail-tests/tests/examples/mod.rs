@@ -1,5 +1,14 @@
+mod combinators;
+mod connect;
+mod cors;
+mod custom_matchers;
+mod grpc_compression;
+mod grpc_health;
+mod grpc_metadata;
 mod grpc_streaming;
 mod grpc_unary;
+mod grpc_web;
 mod headers;
 mod http_streaming;
 mod http_unary;
+mod websocket;
@@ -0,0 +1,176 @@
+use anyhow::Error;
+use futures::{SinkExt, StreamExt};
+use http_body_util::Empty;
+use hyper::body::Bytes;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use mocktail::prelude::*;
+use test_log::test;
+use tokio_tungstenite::{
+    tungstenite::{protocol::Role, Message},
+    WebSocketStream,
+};
+
+#[test(tokio::test)]
+async fn test_websocket() -> Result<(), Error> {
+    let script = WsScript::new()
+        .expect_text("hello")
+        .send_text("hello dan!")
+        .expect_json(serde_json::json!({"action": "subscribe"}))
+        .send_binary(b"\x01\x02".to_vec())
+        .ping("ping")
+        .close(1000, "bye");
+    let received = script.received();
+
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.path("/ws").websocket();
+        then.websocket(script);
+    });
+    mocks.mock(|when, then| {
+        when.path("/forbidden").websocket();
+        then.forbidden();
+    });
+
+    let server = MockServer::new_http("websocket").with_mocks(mocks);
+    server.start().await?;
+
+    let url = format!("ws://127.0.0.1:{}/ws", server.port().unwrap());
+    let (mut ws, response) = tokio_tungstenite::connect_async(url).await?;
+    assert_eq!(response.status(), http::StatusCode::SWITCHING_PROTOCOLS);
+
+    ws.send(Message::text("hello")).await?;
+    assert_eq!(ws.next().await.unwrap()?, Message::text("hello dan!"));
+    ws.send(Message::text(r#"{"action":"subscribe"}"#)).await?;
+    assert_eq!(
+        ws.next().await.unwrap()?,
+        Message::binary(b"\x01\x02".to_vec())
+    );
+    assert_eq!(ws.next().await.unwrap()?, Message::Ping("ping".into()));
+    let Message::Close(Some(frame)) = ws.next().await.unwrap()? else {
+        panic!("expected close frame");
+    };
+    assert_eq!(u16::from(frame.code), 1000);
+    assert_eq!(frame.reason, "bye");
+
+    assert_eq!(
+        &received.messages()[..2],
+        [
+            WsMessage::Text("hello".into()),
+            WsMessage::json(serde_json::json!({"action": "subscribe"})),
+        ]
+    );
+
+    // Upgrade rejected
+    let url = format!("ws://127.0.0.1:{}/forbidden", server.port().unwrap());
+    let result = tokio_tungstenite::connect_async(url).await;
+    assert!(matches!(
+        result,
+        Err(tokio_tungstenite::tungstenite::Error::Http(response))
+            if response.status() == http::StatusCode::FORBIDDEN
+    ));
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_websocket_unexpected_message() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.path("/ws").websocket();
+        then.websocket(WsScript::new().expect_text("hello").send_text("hi"));
+    });
+
+    let server = MockServer::new_http("websocket").with_mocks(mocks);
+    server.start().await?;
+
+    let url = format!("ws://127.0.0.1:{}/ws", server.port().unwrap());
+    let (mut ws, _) = tokio_tungstenite::connect_async(url).await?;
+    ws.send(Message::text("bye")).await?;
+    let Message::Close(Some(frame)) = ws.next().await.unwrap()? else {
+        panic!("expected close frame");
+    };
+    assert_eq!(u16::from(frame.code), 1008);
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_websocket_http2() -> Result<(), Error> {
+    let script = WsScript::new().expect_text("hello").send_text("hello dan!");
+    let received = script.received();
+
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.path("/ws").websocket();
+        then.websocket(script);
+    });
+
+    let server = MockServer::new_http("websocket").with_mocks(mocks);
+    server.start().await?;
+
+    // Extended CONNECT (RFC 8441)
+    let stream = tokio::net::TcpStream::connect(("127.0.0.1", server.port().unwrap())).await?;
+    let (mut sender, conn) =
+        hyper::client::conn::http2::handshake(TokioExecutor::new(), TokioIo::new(stream)).await?;
+    tokio::spawn(conn);
+    let mut request = http::Request::builder()
+        .method(http::Method::CONNECT)
+        .uri(server.url("/ws").as_str())
+        .header("sec-websocket-version", "13")
+        .body(Empty::<Bytes>::new())?;
+    request
+        .extensions_mut()
+        .insert(hyper::ext::Protocol::from_static("websocket"));
+    sender.ready().await?;
+    let mut response = sender.send_request(request).await?;
+    assert_eq!(response.status(), http::StatusCode::OK);
+
+    let upgraded = hyper::upgrade::on(&mut response).await?;
+    let mut ws = WebSocketStream::from_raw_socket(TokioIo::new(upgraded), Role::Client, None).await;
+    ws.send(Message::text("hello")).await?;
+    assert_eq!(ws.next().await.unwrap()?, Message::text("hello dan!"));
+    ws.close(None).await?;
+
+    assert_eq!(received.messages()[0], WsMessage::Text("hello".into()));
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_websocket_invalid_handshake() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock_with_options(1, Some(1), |when, then| {
+        when.path("/ws").websocket();
+        then.websocket(WsScript::new().send_text("hello!"));
+    });
+
+    let server = MockServer::new_http("websocket").with_mocks(mocks);
+    server.start().await?;
+
+    // Invalid handshakes are rejected without using up the mock
+    let client = reqwest::Client::new();
+    let response = client
+        .get(server.url("/ws"))
+        .header("connection", "upgrade")
+        .header("upgrade", "websocket")
+        .header("sec-websocket-version", "13")
+        .send()
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+    let response = client
+        .get(server.url("/ws"))
+        .header("connection", "upgrade")
+        .header("upgrade", "websocket")
+        .header("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ==")
+        .header("sec-websocket-version", "8")
+        .send()
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::UPGRADE_REQUIRED);
+    assert_eq!(response.headers()["sec-websocket-version"], "13");
+
+    let url = format!("ws://127.0.0.1:{}/ws", server.port().unwrap());
+    let (mut ws, _) = tokio_tungstenite::connect_async(url).await?;
+    assert_eq!(ws.next().await.unwrap()?, Message::text("hello!"));
+
+    Ok(())
+}
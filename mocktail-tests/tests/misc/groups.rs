@@ -0,0 +1,63 @@
+use anyhow::Error;
+use mocktail::prelude::*;
+use test_log::test;
+
+#[test(tokio::test)]
+async fn test_groups() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock_in_group("baseline", |when, then| {
+        when.get().path("/user");
+        then.text("dan");
+    });
+    mocks.insert(
+        Mock::new(|when, then| {
+            when.get().path("/user");
+            then.unauthorized();
+        })
+        .with_priority(1)
+        .with_group("auth-failure"),
+    );
+    mocks.disable_group("auth-failure");
+
+    let server = MockServer::new_http("groups").with_mocks(mocks);
+    server.start().await?;
+    let client = reqwest::Client::new();
+
+    let response = client.get(server.url("/user")).send().await?;
+    assert_eq!(response.status(), http::StatusCode::OK);
+
+    // Flip failure mode on
+    server.enable_group("auth-failure");
+    let response = client.get(server.url("/user")).send().await?;
+    assert_eq!(response.status(), http::StatusCode::UNAUTHORIZED);
+
+    // Disable baseline too
+    server.disable_group("auth-failure");
+    server.disable_group("baseline");
+    assert!(!server.handle().is_group_enabled("baseline"));
+    let response = client.get(server.url("/user")).send().await?;
+    assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+
+    // Reset counters
+    server.enable_group("baseline");
+    let handle = server.handle();
+    let count = |group: &str| {
+        handle
+            .mocks()
+            .iter()
+            .filter(|mock| mock.group() == Some(group))
+            .map(|mock| mock.match_count())
+            .sum::<usize>()
+    };
+    assert_eq!(count("baseline"), 1);
+    assert_eq!(count("auth-failure"), 1);
+    server.reset_group("baseline");
+    assert_eq!(count("baseline"), 0);
+    assert_eq!(count("auth-failure"), 1);
+
+    // Remove a group
+    assert_eq!(server.remove_group("auth-failure"), 1);
+    assert_eq!(handle.len(), 1);
+
+    Ok(())
+}
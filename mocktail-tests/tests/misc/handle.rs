@@ -0,0 +1,113 @@
+use std::sync::Arc;
+
+use anyhow::Error;
+use mocktail::prelude::*;
+use test_log::test;
+
+#[test(tokio::test)]
+async fn test_handle() -> Result<(), Error> {
+    let server = Arc::new(MockServer::new_http("handle"));
+    server.start().await?;
+    let handle = server.handle();
+
+    // Insert mocks concurrently from spawned tasks
+    let tasks = (0..10)
+        .map(|i| {
+            let handle = handle.clone();
+            tokio::spawn(async move {
+                handle.mock(|when, then| {
+                    when.get().path(format!("/hello/{i}"));
+                    then.text(format!("hello {i}!"));
+                })
+            })
+        })
+        .collect::<Vec<_>>();
+    let mut ids = Vec::new();
+    for task in tasks {
+        ids.push(task.await?.detach());
+    }
+    assert_eq!(handle.len(), 10);
+    assert!(ids.iter().all(|id| handle.contains(id)));
+
+    let client = reqwest::Client::new();
+    let response = client.get(server.url("/hello/3")).send().await?;
+    assert_eq!(response.text().await?, "hello 3!");
+
+    // Remove a mock
+    let removed = handle.remove(&ids[3]);
+    assert!(removed.is_some_and(|mock| mock.id() == &ids[3]));
+    assert!(!handle.contains(&ids[3]));
+    assert!(handle.get(&ids[4]).is_some());
+    let response = client.get(server.url("/hello/3")).send().await?;
+    assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+
+    // Mocks added through the server are visible to the handle
+    let _world = server.mock(|when, then| {
+        when.get().path("/world");
+        then.text("world!");
+    });
+    assert_eq!(handle.len(), 10);
+
+    handle.clear();
+    assert!(handle.is_empty());
+
+    Ok(())
+}
+
+// The mocks are deliberately locked while sending a request
+#[allow(clippy::await_holding_lock)]
+#[test(tokio::test)]
+async fn test_mock_guard() -> Result<(), Error> {
+    let server = MockServer::new_http("guard");
+    server.start().await?;
+    let client = reqwest::Client::new();
+
+    {
+        let guard = server.mock(|when, then| {
+            when.get().path("/hello");
+            then.text("hello!");
+        });
+        assert!(server.handle().contains(guard.id()));
+        guard.assert_hits(0);
+
+        let response = client.get(server.url("/hello")).send().await?;
+        assert_eq!(response.text().await?, "hello!");
+        let response = client.get(server.url("/hello")).send().await?;
+        assert_eq!(response.text().await?, "hello!");
+        guard.assert_hits(2);
+    }
+
+    // Mock is removed when the guard is dropped
+    assert!(server.handle().is_empty());
+    let response = client.get(server.url("/hello")).send().await?;
+    assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+
+    // Detached mocks are kept
+    let id = server
+        .mock_with_options(1, None, |when, then| {
+            when.get().path("/hello");
+            then.text("hello!");
+        })
+        .detach();
+    assert!(server.handle().contains(&id));
+
+    // Dropping a guard while the mocks are locked does not block
+    let guard = server.mock(|when, then| {
+        when.get().path("/locked");
+        then.text("locked!");
+    });
+    let locked_id = *guard.id();
+    {
+        let handle = server.handle();
+        let mocks = handle.mocks();
+        drop(guard);
+        assert_eq!(mocks.len(), 2);
+        // The removed mock is no longer matched
+        let response = client.get(server.url("/locked")).send().await?;
+        assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+    }
+    assert!(!server.handle().contains(&locked_id));
+    assert_eq!(server.handle().len(), 1);
+
+    Ok(())
+}
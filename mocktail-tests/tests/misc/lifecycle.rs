@@ -0,0 +1,137 @@
+use std::time::Duration;
+
+use anyhow::Error;
+use mocktail::{prelude::*, server::MockServerConfig};
+use test_log::test;
+
+#[test(tokio::test)]
+async fn test_stop_restart() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.get().path("/hello");
+        then.text("hello!");
+    });
+
+    let server = MockServer::new_http("lifecycle").with_mocks(mocks);
+    server.start().await?;
+    assert!(server.start().await.is_err());
+    let port = server.port().unwrap();
+
+    let client = reqwest::Client::new();
+    let response = client.get(server.url("/hello")).send().await?;
+    assert_eq!(response.text().await?, "hello!");
+
+    // Stopped server refuses connections
+    server.stop().await?;
+    assert!(!server.is_running());
+    assert!(server.stop().await.is_err());
+    assert!(client.get(server.url("/hello")).send().await.is_err());
+
+    // Restarted server uses the same port and mocks
+    server.restart().await?;
+    assert!(server.is_running());
+    assert_eq!(server.port(), Some(port));
+    let response = client.get(server.url("/hello")).send().await?;
+    assert_eq!(response.text().await?, "hello!");
+
+    // Restart a running server
+    server.restart().await?;
+    let response = client.get(server.url("/hello")).send().await?;
+    assert_eq!(response.text().await?, "hello!");
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_stop_drains_in_flight_requests() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.get().path("/slow");
+        then.text("done").delay(Duration::from_millis(200));
+    });
+
+    let server = MockServer::new_http("lifecycle").with_mocks(mocks);
+    server.start().await?;
+
+    let client = reqwest::Client::new();
+    let request = tokio::spawn(client.get(server.url("/slow")).send());
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    server.stop().await?;
+
+    let response = request.await??;
+    assert_eq!(response.text().await?, "done");
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_drop_shuts_down() -> Result<(), Error> {
+    let server = MockServer::new_http("lifecycle");
+    server.start().await?;
+    let url = server.url("/hello");
+    drop(server);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let client = reqwest::Client::new();
+    assert!(client.get(url).send().await.is_err());
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_fixed_port() -> Result<(), Error> {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")?
+        .local_addr()?
+        .port();
+    let server =
+        MockServer::new_http("lifecycle").with_config(MockServerConfig::new().with_port(port));
+    server.start().await?;
+    assert_eq!(server.port(), Some(port));
+
+    // Port in use
+    let other =
+        MockServer::new_http("lifecycle").with_config(MockServerConfig::new().with_port(port));
+    assert!(other.start().await.is_err());
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_with_listener() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.get().path("/hello");
+        then.text("hello!");
+    });
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+    let server = MockServer::new_http("lifecycle")
+        .with_mocks(mocks)
+        .with_listener(listener);
+    server.start().await?;
+    assert_eq!(server.addr(), Some(&addr));
+
+    let response = reqwest::get(server.url("/hello")).await?;
+    assert_eq!(response.text().await?, "hello!");
+
+    Ok(())
+}
+
+#[allow(deprecated)]
+#[test(tokio::test)]
+async fn test_deprecated_port_range() -> Result<(), Error> {
+    let config = MockServerConfig {
+        port_range_start: 42000,
+        port_range_end: 42100,
+        bind_max_retries: 99,
+        ..Default::default()
+    };
+    let server = MockServer::new_http("lifecycle").with_config(config);
+    server.start().await?;
+    assert!(server
+        .port()
+        .is_some_and(|port| (42000..42100).contains(&port)));
+
+    Ok(())
+}
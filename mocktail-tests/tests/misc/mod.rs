@@ -1 +1,5 @@
+mod groups;
+mod handle;
+mod lifecycle;
+mod transport;
 mod validation;
@@ -0,0 +1,107 @@
+use anyhow::Error;
+use http_body_util::{BodyExt, Empty};
+use hyper::body::Bytes;
+use mocktail::prelude::*;
+use mocktail_tests::pb::{hello_client::HelloClient, HelloRequest, HelloResponse};
+use test_log::test;
+use tonic::transport::Endpoint;
+
+#[cfg(unix)]
+#[test(tokio::test)]
+async fn test_unix_socket() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.get().path("/hello");
+        then.text("hello!");
+    });
+
+    let path = std::env::temp_dir().join(format!("mocktail-{}.sock", std::process::id()));
+    let server = MockServer::new_http("uds")
+        .with_mocks(mocks)
+        .with_unix_socket(&path);
+    server.start().await?;
+    assert_eq!(server.unix_socket_path(), Some(path.as_path()));
+    assert!(server.addr().is_none());
+
+    let client = reqwest::Client::builder()
+        .unix_socket(path.as_path())
+        .build()?;
+    let response = client.get(server.url("/hello")).send().await?;
+    assert_eq!(response.text().await?, "hello!");
+
+    // Socket file is removed on stop and rebound on restart
+    server.stop().await?;
+    assert!(!path.exists());
+    server.restart().await?;
+    let response = client.get(server.url("/hello")).send().await?;
+    assert_eq!(response.text().await?, "hello!");
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_in_memory_grpc() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.path("/example.Hello/HelloUnary")
+            .pb(HelloRequest { name: "dan".into() });
+        then.pb(HelloResponse {
+            message: "hello dan!".into(),
+        });
+    });
+
+    let server = MockServer::new_grpc("example.Hello")
+        .with_mocks(mocks)
+        .with_in_memory();
+    server.start().await?;
+    assert!(server.addr().is_none());
+
+    let channel = Endpoint::from_static("http://localhost")
+        .connect_with_connector(server.channel())
+        .await?;
+    let mut client = HelloClient::new(channel);
+
+    let response = client
+        .hello_unary(HelloRequest { name: "dan".into() })
+        .await?;
+    assert_eq!(
+        response.into_inner(),
+        HelloResponse {
+            message: "hello dan!".into(),
+        }
+    );
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_in_memory_http() -> Result<(), Error> {
+    let mut mocks = MockSet::new();
+    mocks.mock(|when, then| {
+        when.get().path("/hello");
+        then.text("hello!");
+    });
+
+    // In-memory connections are also served alongside TCP
+    let server = MockServer::new_http("memory").with_mocks(mocks);
+    server.start().await?;
+    let channel = server.channel();
+
+    let (mut sender, conn) = hyper::client::conn::http1::handshake(channel.connect()?).await?;
+    tokio::spawn(conn);
+    let request = http::Request::get(server.url("/hello").as_str()).body(Empty::<Bytes>::new())?;
+    let response = sender.send_request(request).await?;
+    assert_eq!(response.into_body().collect().await?.to_bytes(), "hello!");
+
+    // Channels disconnect on stop and reconnect on restart
+    server.stop().await?;
+    assert!(channel.connect().is_err());
+    server.restart().await?;
+    let (mut sender, conn) = hyper::client::conn::http1::handshake(channel.connect()?).await?;
+    tokio::spawn(conn);
+    let request = http::Request::get(server.url("/hello").as_str()).body(Empty::<Bytes>::new())?;
+    let response = sender.send_request(request).await?;
+    assert_eq!(response.into_body().collect().await?.to_bytes(), "hello!");
+
+    Ok(())
+}
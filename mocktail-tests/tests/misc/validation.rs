@@ -20,10 +20,10 @@ async fn test_grpc_service() -> Result<(), Error> {
     // Invalid content-type
     let response = client.post(server.url("/hello")).send().await?;
     assert_eq!(response.status(), http::StatusCode::UNSUPPORTED_MEDIA_TYPE);
-    assert!(response
-        .headers()
-        .get("accept-post")
-        .is_some_and(|value| value == "application/grpc"));
+    assert!(response.headers().get("accept-post").is_some_and(|value| {
+        let value = value.to_str().unwrap();
+        value.starts_with("application/grpc,") && value.contains("application/connect+proto")
+    }));
 
     Ok(())
 }
@@ -35,10 +35,14 @@ async fn test_http_service() -> Result<(), Error> {
 
     let client = reqwest::Client::builder().http2_prior_knowledge().build()?;
 
-    // Invalid method
+    // All methods are accepted
     let response = client.patch(server.url("/hello")).send().await?;
-    assert_eq!(response.status(), http::StatusCode::METHOD_NOT_ALLOWED);
-    assert!(response.headers().get("allow").is_some());
+    assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+    let response = client
+        .request(http::Method::from_bytes(b"PROPFIND")?, server.url("/hello"))
+        .send()
+        .await?;
+    assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
 
     Ok(())
 }
@@ -5,7 +5,7 @@ use bytes::{Buf, Bytes};
 use futures::Stream;
 use http_body::Frame;
 
-use crate::ext::MessageExt;
+use crate::{ext::MessageExt, sse::SseEvent};
 
 mod buf_list;
 use buf_list::BufList;
@@ -53,6 +53,15 @@ impl Body {
         Self { bufs }
     }
 
+    /// Creates a Server-Sent Events streaming body.
+    pub fn sse(events: impl IntoIterator<Item = impl Into<SseEvent>>) -> Self {
+        let bufs = events
+            .into_iter()
+            .map(|event| event.into().to_bytes())
+            .collect();
+        Self { bufs }
+    }
+
     /// Creates a protobuf body.
     pub fn pb(body: impl prost::Message) -> Self {
         let bytes = body.to_bytes();
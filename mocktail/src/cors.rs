@@ -0,0 +1,184 @@
+//! Cross-Origin Resource Sharing (CORS)
+use std::time::Duration;
+
+use http::{HeaderMap, HeaderValue};
+
+/// A CORS policy for the HTTP mock server.
+///
+/// By default, any origin is allowed and the requested method
+/// and headers of preflight requests are mirrored.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Cors {
+    origins: Option<Vec<String>>,
+    methods: Option<Vec<String>>,
+    headers: Option<Vec<String>>,
+    expose_headers: Vec<String>,
+    credentials: bool,
+    max_age: Option<Duration>,
+}
+
+impl Cors {
+    /// Creates a permissive CORS policy.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the allowed origins.
+    pub fn with_origins(mut self, origins: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.origins = Some(origins.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Sets the allowed methods.
+    pub fn with_methods(mut self, methods: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.methods = Some(methods.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Sets the allowed request headers.
+    pub fn with_headers(mut self, headers: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.headers = Some(headers.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Sets the response headers exposed to the client.
+    pub fn with_expose_headers(
+        mut self,
+        headers: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.expose_headers = headers.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Allows credentials (cookies, authorization headers).
+    pub fn with_credentials(mut self, credentials: bool) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    /// Sets how long preflight results may be cached.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Returns true if the origin is allowed.
+    pub fn is_origin_allowed(&self, origin: &str) -> bool {
+        self.origins
+            .as_ref()
+            .is_none_or(|origins| origins.iter().any(|allowed| allowed == origin))
+    }
+
+    /// Returns true if the request is a preflight request.
+    pub fn is_preflight(method: &http::Method, headers: &HeaderMap) -> bool {
+        method == http::Method::OPTIONS
+            && headers.contains_key("origin")
+            && headers.contains_key("access-control-request-method")
+    }
+
+    /// Returns the headers of a preflight response, if the origin is allowed.
+    pub fn preflight_headers(&self, request_headers: &HeaderMap) -> Option<HeaderMap> {
+        let mut headers = self.response_headers(request_headers)?;
+        let methods = match &self.methods {
+            Some(methods) => HeaderValue::from_str(&methods.join(", ")).ok(),
+            None => request_headers
+                .get("access-control-request-method")
+                .cloned(),
+        };
+        if let Some(methods) = methods {
+            headers.insert("access-control-allow-methods", methods);
+        }
+        let allow_headers = match &self.headers {
+            Some(allow_headers) => HeaderValue::from_str(&allow_headers.join(", ")).ok(),
+            None => request_headers
+                .get("access-control-request-headers")
+                .cloned(),
+        };
+        if let Some(allow_headers) = allow_headers {
+            headers.insert("access-control-allow-headers", allow_headers);
+        }
+        if let Some(max_age) = self.max_age {
+            headers.insert(
+                "access-control-max-age",
+                HeaderValue::from(max_age.as_secs()),
+            );
+        }
+        Some(headers)
+    }
+
+    /// Returns the CORS headers of a response, if the request has an allowed origin.
+    pub fn response_headers(&self, request_headers: &HeaderMap) -> Option<HeaderMap> {
+        let origin = request_headers.get("origin")?;
+        if !self.is_origin_allowed(origin.to_str().ok()?) {
+            return None;
+        }
+        let mut headers = HeaderMap::new();
+        if self.origins.is_none() && !self.credentials {
+            headers.insert("access-control-allow-origin", HeaderValue::from_static("*"));
+        } else {
+            headers.insert("access-control-allow-origin", origin.clone());
+            headers.insert("vary", HeaderValue::from_static("origin"));
+        }
+        if self.credentials {
+            headers.insert(
+                "access-control-allow-credentials",
+                HeaderValue::from_static("true"),
+            );
+        }
+        if !self.expose_headers.is_empty() {
+            if let Ok(value) = HeaderValue::from_str(&self.expose_headers.join(", ")) {
+                headers.insert("access-control-expose-headers", value);
+            }
+        }
+        Some(headers)
+    }
+
+    /// Adds the CORS headers to a response, if the request has an allowed origin.
+    ///
+    /// `origin` is appended to any `vary` header of the response.
+    pub fn add_response_headers(
+        &self,
+        request_headers: &HeaderMap,
+        response_headers: &mut HeaderMap,
+    ) {
+        let Some(headers) = self.response_headers(request_headers) else {
+            return;
+        };
+        for (name, value) in &headers {
+            if name == "vary" {
+                append_vary(response_headers, value);
+            } else {
+                response_headers.insert(name, value.clone());
+            }
+        }
+    }
+}
+
+/// Appends a value to the `vary` header, unless it is already listed.
+fn append_vary(headers: &mut HeaderMap, value: &HeaderValue) {
+    let Ok(value) = value.to_str() else {
+        return;
+    };
+    let vary = headers
+        .get_all("vary")
+        .iter()
+        .filter_map(|vary| vary.to_str().ok())
+        .flat_map(|vary| vary.split(','))
+        .map(str::trim)
+        .filter(|vary| !vary.is_empty())
+        .collect::<Vec<_>>();
+    if vary
+        .iter()
+        .any(|vary| *vary == "*" || vary.eq_ignore_ascii_case(value))
+    {
+        return;
+    }
+    let vary = vary
+        .into_iter()
+        .chain([value])
+        .collect::<Vec<_>>()
+        .join(", ");
+    if let Ok(vary) = HeaderValue::from_str(&vary) {
+        headers.insert("vary", vary);
+    }
+}
@@ -0,0 +1,10 @@
+//! gRPC protocol support
+pub mod codec;
+pub use codec::{GrpcFrame, GrpcFrameDecoder};
+pub mod compression;
+pub use compression::CompressionEncoding;
+pub mod error_details;
+pub use error_details::ErrorDetail;
+pub mod health;
+pub use health::ServingStatus;
+pub mod timeout;
@@ -0,0 +1,115 @@
+//! gRPC message framing
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+/// Length-prefixed message header size: 1 byte compressed flag and 4 bytes message length.
+pub const HEADER_SIZE: usize = 5;
+
+/// A gRPC length-prefixed message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrpcFrame {
+    /// Compressed flag.
+    pub compressed: bool,
+    /// Message data.
+    pub data: Bytes,
+}
+
+impl GrpcFrame {
+    /// Encodes the message with its length prefix.
+    pub fn encode(&self) -> Bytes {
+        encode_frame(self.compressed, &self.data)
+    }
+}
+
+/// A decoder that reassembles length-prefixed gRPC messages from a request stream,
+/// regardless of how the data is split into chunks.
+#[derive(Debug, Default)]
+pub struct GrpcFrameDecoder {
+    buf: BytesMut,
+}
+
+impl GrpcFrameDecoder {
+    /// Creates a new decoder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a chunk of data and returns the messages completed by it.
+    pub fn decode(&mut self, chunk: impl AsRef<[u8]>) -> Vec<GrpcFrame> {
+        self.buf.extend_from_slice(chunk.as_ref());
+        let mut frames = Vec::new();
+        while self.buf.len() >= HEADER_SIZE {
+            let len = u32::from_be_bytes(self.buf[1..HEADER_SIZE].try_into().unwrap()) as usize;
+            if self.buf.len() < HEADER_SIZE + len {
+                break;
+            }
+            let compressed = self.buf.get_u8() & 1 == 1;
+            self.buf.advance(HEADER_SIZE - 1);
+            let data = self.buf.split_to(len).freeze();
+            frames.push(GrpcFrame { compressed, data });
+        }
+        frames
+    }
+
+    /// Returns true if data of an incomplete message is buffered.
+    pub fn has_remaining(&self) -> bool {
+        !self.buf.is_empty()
+    }
+
+    /// Returns the buffered data of an incomplete message.
+    pub fn remaining(&self) -> &[u8] {
+        &self.buf
+    }
+}
+
+/// Encodes data as a length-prefixed message.
+pub fn encode_frame(compressed: bool, data: &[u8]) -> Bytes {
+    let mut buf = BytesMut::with_capacity(HEADER_SIZE + data.len());
+    buf.put_u8(compressed as u8);
+    buf.put_u32(data.len() as u32);
+    buf.put_slice(data);
+    buf.freeze()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_split_frames() {
+        let mut data = BytesMut::new();
+        data.extend(encode_frame(false, b"hello"));
+        data.extend(encode_frame(true, b""));
+        data.extend(encode_frame(false, b"world!"));
+
+        // Decode one byte at a time
+        let mut decoder = GrpcFrameDecoder::new();
+        let frames = data
+            .chunks(1)
+            .flat_map(|chunk| decoder.decode(chunk))
+            .collect::<Vec<_>>();
+        assert!(!decoder.has_remaining());
+        assert_eq!(
+            frames,
+            [
+                GrpcFrame {
+                    compressed: false,
+                    data: Bytes::from_static(b"hello"),
+                },
+                GrpcFrame {
+                    compressed: true,
+                    data: Bytes::new(),
+                },
+                GrpcFrame {
+                    compressed: false,
+                    data: Bytes::from_static(b"world!"),
+                },
+            ]
+        );
+
+        // Decode all at once, with an incomplete message
+        let mut decoder = GrpcFrameDecoder::new();
+        let frames = decoder.decode(&data[..data.len() - 1]);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(decoder.remaining(), &data[data.len() - 11..data.len() - 1]);
+    }
+}
@@ -0,0 +1,148 @@
+//! gRPC message compression
+//!
+//! Encodings are enabled by the `gzip`, `deflate` and `zstd` features.
+#[cfg(any(feature = "gzip", feature = "deflate"))]
+use std::io::{Read, Write};
+
+#[cfg(feature = "gzip")]
+use flate2::{read::GzDecoder, write::GzEncoder};
+#[cfg(feature = "deflate")]
+use flate2::{read::ZlibDecoder, write::ZlibEncoder};
+
+use crate::Error;
+
+/// Represents a gRPC message compression encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CompressionEncoding {
+    Gzip,
+    Deflate,
+    Zstd,
+}
+
+impl CompressionEncoding {
+    /// Supported compression encodings, as enabled by features.
+    pub const ALL: &'static [CompressionEncoding] = &[
+        #[cfg(feature = "gzip")]
+        CompressionEncoding::Gzip,
+        #[cfg(feature = "deflate")]
+        CompressionEncoding::Deflate,
+        #[cfg(feature = "zstd")]
+        CompressionEncoding::Zstd,
+    ];
+
+    /// Returns true if the encoding is enabled by its feature.
+    pub fn is_supported(&self) -> bool {
+        Self::ALL.contains(self)
+    }
+
+    /// Returns the `grpc-encoding` header value.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CompressionEncoding::Gzip => "gzip",
+            CompressionEncoding::Deflate => "deflate",
+            CompressionEncoding::Zstd => "zstd",
+        }
+    }
+
+    /// Parses a `grpc-encoding` header value.
+    ///
+    /// Returns `None` for `identity`, i.e. no compression.
+    pub fn from_header_value(value: &str) -> Result<Option<Self>, Error> {
+        let encoding = match value.trim() {
+            "identity" => return Ok(None),
+            "gzip" => CompressionEncoding::Gzip,
+            "deflate" => CompressionEncoding::Deflate,
+            "zstd" => CompressionEncoding::Zstd,
+            value => {
+                return Err(Error::Invalid(format!(
+                    "unsupported compression encoding {value}"
+                )))
+            }
+        };
+        if !encoding.is_supported() {
+            return Err(encoding.unsupported());
+        }
+        Ok(Some(encoding))
+    }
+
+    /// Parses a `grpc-accept-encoding` header value, ignoring unsupported encodings.
+    pub fn from_accept_header_value(value: &str) -> Vec<Self> {
+        value
+            .split(',')
+            .filter_map(|value| Self::from_header_value(value).ok().flatten())
+            .collect()
+    }
+
+    /// Returns the `grpc-accept-encoding` header value of supported encodings.
+    pub fn accept_header_value() -> String {
+        std::iter::once("identity")
+            .chain(Self::ALL.iter().map(|encoding| encoding.as_str()))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Compresses data.
+    #[cfg_attr(
+        not(any(feature = "gzip", feature = "deflate", feature = "zstd")),
+        allow(unused_variables)
+    )]
+    pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        match self {
+            #[cfg(feature = "gzip")]
+            CompressionEncoding::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data)?;
+                Ok(encoder.finish()?)
+            }
+            #[cfg(feature = "deflate")]
+            CompressionEncoding::Deflate => {
+                let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data)?;
+                Ok(encoder.finish()?)
+            }
+            #[cfg(feature = "zstd")]
+            CompressionEncoding::Zstd => Ok(zstd::encode_all(data, 0)?),
+            #[allow(unreachable_patterns)]
+            _ => Err(self.unsupported()),
+        }
+    }
+
+    /// Decompresses data.
+    #[cfg_attr(
+        not(any(feature = "gzip", feature = "deflate", feature = "zstd")),
+        allow(unused_variables)
+    )]
+    pub fn decompress(&self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        match self {
+            #[cfg(feature = "gzip")]
+            CompressionEncoding::Gzip => {
+                let mut buf = Vec::new();
+                GzDecoder::new(data).read_to_end(&mut buf)?;
+                Ok(buf)
+            }
+            #[cfg(feature = "deflate")]
+            CompressionEncoding::Deflate => {
+                let mut buf = Vec::new();
+                ZlibDecoder::new(data).read_to_end(&mut buf)?;
+                Ok(buf)
+            }
+            #[cfg(feature = "zstd")]
+            CompressionEncoding::Zstd => Ok(zstd::decode_all(data)?),
+            #[allow(unreachable_patterns)]
+            _ => Err(self.unsupported()),
+        }
+    }
+
+    /// Returns the error of an encoding that is not enabled.
+    fn unsupported(&self) -> Error {
+        Error::Invalid(format!(
+            "unsupported compression encoding {self}, enable the `{self}` feature"
+        ))
+    }
+}
+
+impl std::fmt::Display for CompressionEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
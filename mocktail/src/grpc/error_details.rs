@@ -0,0 +1,254 @@
+//! gRPC rich error details
+//!
+//! Implements the `google.rpc.Status` error model sent in the
+//! `grpc-status-details-bin` trailer, with a subset of the standard
+//! error detail messages defined in
+//! [error_details.proto](https://github.com/googleapis/googleapis/blob/master/google/rpc/error_details.proto).
+use std::{collections::HashMap, time::Duration};
+
+use base64::{engine::general_purpose::STANDARD_NO_PAD, Engine};
+use prost::{Message, Name};
+use prost_types::Any;
+
+use crate::status::Code;
+
+/// Represents a `google.rpc.Status`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Status {
+    #[prost(int32, tag = "1")]
+    pub code: i32,
+    #[prost(string, tag = "2")]
+    pub message: String,
+    #[prost(message, repeated, tag = "3")]
+    pub details: Vec<Any>,
+}
+
+impl Status {
+    /// Creates a status with error details.
+    pub fn new(code: Code, message: impl Into<String>, details: &[ErrorDetail]) -> Self {
+        Self {
+            code: code as i32,
+            message: message.into(),
+            details: details.iter().map(ErrorDetail::to_any).collect(),
+        }
+    }
+
+    /// Returns the base64 encoded `grpc-status-details-bin` header value.
+    pub fn to_header_value(&self) -> http::HeaderValue {
+        let value = STANDARD_NO_PAD.encode(self.encode_to_vec());
+        http::HeaderValue::try_from(value).unwrap()
+    }
+}
+
+/// Represents a gRPC error detail.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorDetail {
+    BadRequest(BadRequest),
+    RetryInfo(RetryInfo),
+    ErrorInfo(ErrorInfo),
+    QuotaFailure(QuotaFailure),
+    /// Any other error detail message.
+    Any(Any),
+}
+
+impl ErrorDetail {
+    /// Creates a `google.rpc.BadRequest` detail from field and description pairs.
+    pub fn bad_request(
+        violations: impl IntoIterator<Item = (impl Into<String>, impl Into<String>)>,
+    ) -> Self {
+        let field_violations = violations
+            .into_iter()
+            .map(|(field, description)| FieldViolation {
+                field: field.into(),
+                description: description.into(),
+            })
+            .collect();
+        Self::BadRequest(BadRequest { field_violations })
+    }
+
+    /// Creates a `google.rpc.RetryInfo` detail.
+    pub fn retry_info(retry_delay: Duration) -> Self {
+        Self::RetryInfo(RetryInfo {
+            retry_delay: Some(retry_delay.try_into().unwrap()),
+        })
+    }
+
+    /// Creates a `google.rpc.ErrorInfo` detail.
+    pub fn error_info(
+        reason: impl Into<String>,
+        domain: impl Into<String>,
+        metadata: impl IntoIterator<Item = (impl Into<String>, impl Into<String>)>,
+    ) -> Self {
+        Self::ErrorInfo(ErrorInfo {
+            reason: reason.into(),
+            domain: domain.into(),
+            metadata: metadata
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        })
+    }
+
+    /// Creates a `google.rpc.QuotaFailure` detail from subject and description pairs.
+    pub fn quota_failure(
+        violations: impl IntoIterator<Item = (impl Into<String>, impl Into<String>)>,
+    ) -> Self {
+        let violations = violations
+            .into_iter()
+            .map(|(subject, description)| QuotaViolation {
+                subject: subject.into(),
+                description: description.into(),
+            })
+            .collect();
+        Self::QuotaFailure(QuotaFailure { violations })
+    }
+
+    /// Creates a detail from any protobuf message.
+    pub fn any(message: &impl Name) -> Self {
+        Self::Any(Any::from_msg(message).unwrap())
+    }
+
+    /// Returns the detail packed as [`Any`].
+    pub fn to_any(&self) -> Any {
+        match self {
+            ErrorDetail::BadRequest(detail) => Any::from_msg(detail),
+            ErrorDetail::RetryInfo(detail) => Any::from_msg(detail),
+            ErrorDetail::ErrorInfo(detail) => Any::from_msg(detail),
+            ErrorDetail::QuotaFailure(detail) => Any::from_msg(detail),
+            ErrorDetail::Any(any) => Ok(any.clone()),
+        }
+        .unwrap()
+    }
+}
+
+impl From<BadRequest> for ErrorDetail {
+    fn from(value: BadRequest) -> Self {
+        Self::BadRequest(value)
+    }
+}
+
+impl From<RetryInfo> for ErrorDetail {
+    fn from(value: RetryInfo) -> Self {
+        Self::RetryInfo(value)
+    }
+}
+
+impl From<ErrorInfo> for ErrorDetail {
+    fn from(value: ErrorInfo) -> Self {
+        Self::ErrorInfo(value)
+    }
+}
+
+impl From<QuotaFailure> for ErrorDetail {
+    fn from(value: QuotaFailure) -> Self {
+        Self::QuotaFailure(value)
+    }
+}
+
+impl From<Any> for ErrorDetail {
+    fn from(value: Any) -> Self {
+        Self::Any(value)
+    }
+}
+
+/// Represents a `google.rpc.BadRequest`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct BadRequest {
+    #[prost(message, repeated, tag = "1")]
+    pub field_violations: Vec<FieldViolation>,
+}
+
+/// Represents a `google.rpc.BadRequest.FieldViolation`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct FieldViolation {
+    #[prost(string, tag = "1")]
+    pub field: String,
+    #[prost(string, tag = "2")]
+    pub description: String,
+}
+
+/// Represents a `google.rpc.RetryInfo`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct RetryInfo {
+    #[prost(message, optional, tag = "1")]
+    pub retry_delay: Option<prost_types::Duration>,
+}
+
+/// Represents a `google.rpc.ErrorInfo`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ErrorInfo {
+    #[prost(string, tag = "1")]
+    pub reason: String,
+    #[prost(string, tag = "2")]
+    pub domain: String,
+    #[prost(map = "string, string", tag = "3")]
+    pub metadata: HashMap<String, String>,
+}
+
+/// Represents a `google.rpc.QuotaFailure`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct QuotaFailure {
+    #[prost(message, repeated, tag = "1")]
+    pub violations: Vec<QuotaViolation>,
+}
+
+/// Represents a `google.rpc.QuotaFailure.Violation`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct QuotaViolation {
+    #[prost(string, tag = "1")]
+    pub subject: String,
+    #[prost(string, tag = "2")]
+    pub description: String,
+}
+
+/// Implements [`Name`] for a `google.rpc` message.
+macro_rules! impl_name {
+    ($($ty:ident),*) => {
+        $(
+            impl Name for $ty {
+                const NAME: &'static str = stringify!($ty);
+                const PACKAGE: &'static str = "google.rpc";
+
+                fn type_url() -> String {
+                    format!("type.googleapis.com/{}", Self::full_name())
+                }
+            }
+        )*
+    };
+}
+
+impl_name!(Status, BadRequest, RetryInfo, ErrorInfo, QuotaFailure);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_roundtrip() {
+        let status = Status::new(
+            Code::Unavailable,
+            "try again",
+            &[
+                ErrorDetail::retry_info(Duration::from_millis(1500)),
+                ErrorDetail::bad_request([("name", "must not be empty")]),
+            ],
+        );
+        let value = status.to_header_value();
+        let bytes = STANDARD_NO_PAD.decode(value.as_bytes()).unwrap();
+        let decoded = Status::decode(bytes.as_slice()).unwrap();
+        assert_eq!(decoded, status);
+        assert_eq!(decoded.code, Code::Unavailable as i32);
+        assert_eq!(
+            decoded.details[0].type_url,
+            "type.googleapis.com/google.rpc.RetryInfo"
+        );
+        let retry_info = decoded.details[0].to_msg::<RetryInfo>().unwrap();
+        assert_eq!(
+            retry_info.retry_delay,
+            Some(prost_types::Duration {
+                seconds: 1,
+                nanos: 500_000_000
+            })
+        );
+    }
+}
@@ -0,0 +1,95 @@
+//! gRPC health checking service
+//!
+//! Implements `grpc.health.v1.Health` as defined in the
+//! [gRPC health checking protocol](https://github.com/grpc/grpc/blob/master/doc/health-checking.md).
+use std::collections::HashMap;
+
+use tokio::sync::watch;
+
+/// Health check service path prefix.
+pub const SERVICE_PATH: &str = "/grpc.health.v1.Health/";
+/// `Check` method path.
+pub const CHECK_PATH: &str = "/grpc.health.v1.Health/Check";
+/// `Watch` method path.
+pub const WATCH_PATH: &str = "/grpc.health.v1.Health/Watch";
+
+/// Represents a `grpc.health.v1.HealthCheckRequest`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct HealthCheckRequest {
+    #[prost(string, tag = "1")]
+    pub service: String,
+}
+
+/// Represents a `grpc.health.v1.HealthCheckResponse`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct HealthCheckResponse {
+    #[prost(enumeration = "ServingStatus", tag = "1")]
+    pub status: i32,
+}
+
+impl HealthCheckResponse {
+    pub fn new(status: ServingStatus) -> Self {
+        Self {
+            status: status as i32,
+        }
+    }
+}
+
+/// Represents a gRPC health serving status.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum ServingStatus {
+    Unknown = 0,
+    Serving = 1,
+    NotServing = 2,
+    /// Used only by the `Watch` method.
+    ServiceUnknown = 3,
+}
+
+/// Serving statuses of services.
+///
+/// The overall server status is registered as the empty service name
+/// and defaults to [`ServingStatus::Serving`].
+#[derive(Debug)]
+pub struct HealthState {
+    statuses: watch::Sender<HashMap<String, ServingStatus>>,
+}
+
+impl HealthState {
+    /// Creates a new health state.
+    pub fn new() -> Self {
+        let statuses = HashMap::from([(String::new(), ServingStatus::Serving)]);
+        Self {
+            statuses: watch::Sender::new(statuses),
+        }
+    }
+
+    /// Returns the serving status of a service.
+    pub fn get(&self, service: &str) -> Option<ServingStatus> {
+        self.statuses.borrow().get(service).copied()
+    }
+
+    /// Sets the serving status of a service and notifies watchers if it changed.
+    pub fn set(&self, service: impl Into<String>, status: ServingStatus) {
+        let service: String = service.into();
+        self.statuses
+            .send_if_modified(|statuses| statuses.insert(service, status) != Some(status));
+    }
+
+    /// Removes a service.
+    pub fn remove(&self, service: &str) {
+        self.statuses
+            .send_if_modified(|statuses| statuses.remove(service).is_some());
+    }
+
+    /// Subscribes to status changes.
+    pub fn subscribe(&self) -> watch::Receiver<HashMap<String, ServingStatus>> {
+        self.statuses.subscribe()
+    }
+}
+
+impl Default for HealthState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
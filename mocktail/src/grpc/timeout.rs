@@ -0,0 +1,47 @@
+//! gRPC deadlines
+//!
+//! Parses the `grpc-timeout` header as defined in the
+//! [gRPC over HTTP2 protocol](https://github.com/grpc/grpc/blob/master/doc/PROTOCOL-HTTP2.md).
+use std::time::Duration;
+
+/// Parses a `grpc-timeout` header value, e.g. `100m`.
+pub fn parse_timeout(value: &str) -> Option<Duration> {
+    // Value is at most 8 ASCII digits followed by a unit
+    if value.len() < 2 || value.len() > 9 || !value.is_ascii() {
+        return None;
+    }
+    let (value, unit) = value.split_at(value.len() - 1);
+    let value: u64 = value.parse().ok()?;
+    let timeout = match unit {
+        "H" => Duration::from_secs(value * 60 * 60),
+        "M" => Duration::from_secs(value * 60),
+        "S" => Duration::from_secs(value),
+        "m" => Duration::from_millis(value),
+        "u" => Duration::from_micros(value),
+        "n" => Duration::from_nanos(value),
+        _ => return None,
+    };
+    Some(timeout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timeout() {
+        assert_eq!(parse_timeout("1H"), Some(Duration::from_secs(3600)));
+        assert_eq!(parse_timeout("2M"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_timeout("3S"), Some(Duration::from_secs(3)));
+        assert_eq!(parse_timeout("100m"), Some(Duration::from_millis(100)));
+        assert_eq!(
+            parse_timeout("99999999u"),
+            Some(Duration::from_micros(99999999))
+        );
+        assert_eq!(parse_timeout("5n"), Some(Duration::from_nanos(5)));
+        assert_eq!(parse_timeout("100"), None);
+        assert_eq!(parse_timeout("m"), None);
+        assert_eq!(parse_timeout("100x"), None);
+        assert_eq!(parse_timeout("123456789S"), None);
+    }
+}
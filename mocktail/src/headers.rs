@@ -100,6 +100,18 @@ where
     }
 }
 
+impl<T, U> Extend<(T, U)> for Headers
+where
+    T: Into<HeaderName>,
+    U: Into<HeaderValue>,
+{
+    fn extend<I: IntoIterator<Item = (T, U)>>(&mut self, iter: I) {
+        for (name, value) in iter {
+            self.insert(name, value);
+        }
+    }
+}
+
 impl From<Headers> for http::HeaderMap {
     fn from(value: Headers) -> Self {
         value
@@ -1,5 +1,7 @@
 #![doc = include_str!("../README.md")]
 pub mod body;
+pub mod cors;
+pub mod grpc;
 mod headers;
 pub use headers::Headers;
 pub mod matchers;
@@ -13,11 +15,15 @@ pub use request::{Method, Request};
 mod response;
 pub use response::Response;
 pub mod server;
+pub mod sse;
 mod status;
 pub use status::{Code, StatusCode};
+pub mod websocket;
 pub mod prelude {
     pub use crate::{
         body::Body,
+        cors::Cors,
+        grpc::{CompressionEncoding, ErrorDetail, ServingStatus},
         headers::Headers,
         matchers::*,
         mock::Mock,
@@ -25,7 +31,9 @@ pub mod prelude {
         request::{Method, Request},
         response::Response,
         server::MockServer,
+        sse::SseEvent,
         status::{Code, StatusCode},
+        websocket::{WsMatcher, WsMessage, WsScript},
     };
 }
 mod ext;
@@ -1,8 +1,15 @@
 //! Mock request matchers
-use std::{any::Any, borrow::Cow, cmp::Ordering};
+use std::{any::Any, borrow::Cow, cmp::Ordering, future::Future, sync::Arc, time::Duration};
+
+use bytes::Bytes;
+use futures::{future::BoxFuture, FutureExt};
 
 use super::{body::Body, headers::Headers, request::Request};
-use crate::request::Method;
+use crate::{
+    grpc::timeout::parse_timeout,
+    request::Method,
+    sse::{self, SseEvent},
+};
 
 /// A matcher.
 pub trait Matcher: std::fmt::Debug + Send + Sync + 'static + AsMatcherEq {
@@ -10,6 +17,22 @@ pub trait Matcher: std::fmt::Debug + Send + Sync + 'static + AsMatcherEq {
     fn name(&self) -> &str;
     /// Evaluates a match condition.
     fn matches(&self, req: &Request) -> bool;
+    /// Evaluates a match condition, returning the reason on failure.
+    fn check(&self, req: &Request) -> Result<(), String> {
+        if self.matches(req) {
+            Ok(())
+        } else {
+            Err(format!("{} did not match", self.name()))
+        }
+    }
+    /// Returns `true` if the matcher must be evaluated with [`Matcher::check_async`].
+    fn is_async(&self) -> bool {
+        false
+    }
+    /// Evaluates a match condition asynchronously, returning the reason on failure.
+    fn check_async<'a>(&'a self, req: &'a Request) -> BoxFuture<'a, Result<(), String>> {
+        std::future::ready(self.check(req)).boxed()
+    }
 }
 
 /// Any matcher.
@@ -97,6 +120,82 @@ pub fn body(body: Body) -> BodyMatcher {
     BodyMatcher(body)
 }
 
+/// WebSocket upgrade matcher.
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct WebSocketMatcher;
+
+impl Matcher for WebSocketMatcher {
+    fn name(&self) -> &str {
+        "websocket"
+    }
+    fn matches(&self, req: &Request) -> bool {
+        req.is_websocket()
+    }
+}
+
+/// Matches a WebSocket upgrade request.
+pub fn websocket() -> WebSocketMatcher {
+    WebSocketMatcher
+}
+
+/// Server-Sent Events body matcher.
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct SseMatcher(Vec<SseEvent>);
+
+impl Matcher for SseMatcher {
+    fn name(&self) -> &str {
+        "sse"
+    }
+    fn matches(&self, req: &Request) -> bool {
+        sse::parse(&req.body.clone().as_bytes()) == self.0
+    }
+}
+
+/// Matches a request body of Server-Sent Events.
+pub fn sse(events: impl IntoIterator<Item = impl Into<SseEvent>>) -> SseMatcher {
+    SseMatcher(events.into_iter().map(Into::into).collect())
+}
+
+/// gRPC message matcher.
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct MessageMatcher(usize, Bytes);
+
+impl Matcher for MessageMatcher {
+    fn name(&self) -> &str {
+        "message"
+    }
+    fn matches(&self, req: &Request) -> bool {
+        req.messages.get(self.0) == Some(&self.1)
+    }
+}
+
+/// Matches the gRPC request message at index.
+///
+/// Indices count from the start of the request stream, also for bidi mocks.
+pub fn message(index: usize, message: impl Into<Bytes>) -> MessageMatcher {
+    MessageMatcher(index, message.into())
+}
+
+/// gRPC messages matcher.
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct MessagesMatcher(Vec<Bytes>);
+
+impl Matcher for MessagesMatcher {
+    fn name(&self) -> &str {
+        "messages"
+    }
+    fn matches(&self, req: &Request) -> bool {
+        self.0 == req.messages
+    }
+}
+
+/// Matches all gRPC request messages.
+///
+/// For bidi mocks, these are the messages received so far.
+pub fn messages(messages: impl IntoIterator<Item = impl Into<Bytes>>) -> MessagesMatcher {
+    MessagesMatcher(messages.into_iter().map(Into::into).collect())
+}
+
 /// Headers matcher.
 #[derive(Debug, PartialEq, PartialOrd)]
 pub struct HeadersMatcher(Headers);
@@ -165,6 +264,45 @@ pub fn header_exists(name: impl Into<String>) -> HeaderExistsMatcher {
     HeaderExistsMatcher(name.into())
 }
 
+/// Origin matcher.
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct OriginMatcher(String);
+
+impl Matcher for OriginMatcher {
+    fn name(&self) -> &str {
+        "origin"
+    }
+    fn matches(&self, req: &Request) -> bool {
+        req.headers.contains("origin", &self.0)
+    }
+}
+
+/// Matches a request with an `Origin` header.
+pub fn origin(origin: impl Into<String>) -> OriginMatcher {
+    OriginMatcher(origin.into())
+}
+
+/// gRPC timeout matcher.
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct GrpcTimeoutMatcher(Duration);
+
+impl Matcher for GrpcTimeoutMatcher {
+    fn name(&self) -> &str {
+        "grpc_timeout"
+    }
+    fn matches(&self, req: &Request) -> bool {
+        req.headers
+            .get("grpc-timeout")
+            .and_then(|value| parse_timeout(value.as_ref()))
+            .is_some_and(|timeout| timeout <= self.0)
+    }
+}
+
+/// Matches a gRPC request with a deadline of at most `timeout`.
+pub fn grpc_timeout_at_most(timeout: Duration) -> GrpcTimeoutMatcher {
+    GrpcTimeoutMatcher(timeout)
+}
+
 /// Query params matcher.
 #[derive(Debug, PartialEq, PartialOrd)]
 pub struct QueryParamsMatcher(Vec<(Cow<'static, str>, Cow<'static, str>)>);
@@ -224,6 +362,370 @@ pub fn query_param_exists(key: impl Into<String>) -> QueryParamExistsMatcher {
     QueryParamExistsMatcher(key.into())
 }
 
+/// Bidirectional stream matcher.
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct BidiMatcher;
+
+impl Matcher for BidiMatcher {
+    fn name(&self) -> &str {
+        "bidi"
+    }
+    fn matches(&self, _req: &Request) -> bool {
+        true
+    }
+}
+
+/// Marks a gRPC mock as matching the messages of a bidirectional stream individually.
+pub fn bidi() -> BidiMatcher {
+    BidiMatcher
+}
+
+/// Negation matcher.
+#[derive(Debug)]
+pub struct NotMatcher(Arc<dyn Matcher>);
+
+impl PartialEq for NotMatcher {
+    fn eq(&self, other: &Self) -> bool {
+        *self.0 == *other.0
+    }
+}
+
+impl PartialOrd for NotMatcher {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.0.cmp(&other.0))
+    }
+}
+
+impl NotMatcher {
+    pub(crate) fn new(matcher: Arc<dyn Matcher>) -> Self {
+        Self(matcher)
+    }
+}
+
+impl Matcher for NotMatcher {
+    fn name(&self) -> &str {
+        "not"
+    }
+    fn matches(&self, req: &Request) -> bool {
+        !self.is_async() && !self.0.matches(req)
+    }
+    fn check(&self, req: &Request) -> Result<(), String> {
+        if self.is_async() {
+            return Err(format!("{} must be evaluated asynchronously", self.name()));
+        }
+        negate(self.0.name(), self.0.check(req))
+    }
+    fn is_async(&self) -> bool {
+        self.0.is_async()
+    }
+    fn check_async<'a>(&'a self, req: &'a Request) -> BoxFuture<'a, Result<(), String>> {
+        async move { negate(self.0.name(), self.0.check_async(req).await) }.boxed()
+    }
+}
+
+/// Negates the outcome of a matcher.
+fn negate(name: &str, result: Result<(), String>) -> Result<(), String> {
+    match result {
+        Ok(()) => Err(format!("{name} matched")),
+        Err(_) => Ok(()),
+    }
+}
+
+/// Matches a request that does not match a matcher.
+pub fn not(matcher: impl Matcher) -> NotMatcher {
+    NotMatcher(Arc::new(matcher))
+}
+
+/// Any of matcher.
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct AnyOfMatcher(Vec<Arc<dyn Matcher>>);
+
+impl Matcher for AnyOfMatcher {
+    fn name(&self) -> &str {
+        "any_of"
+    }
+    fn matches(&self, req: &Request) -> bool {
+        !self.is_async() && self.0.iter().any(|matcher| matcher.matches(req))
+    }
+    fn check(&self, req: &Request) -> Result<(), String> {
+        if self.is_async() {
+            return Err(format!("{} must be evaluated asynchronously", self.name()));
+        }
+        let mut reasons = Vec::new();
+        for matcher in &self.0 {
+            match matcher.check(req) {
+                Ok(()) => return Ok(()),
+                Err(reason) => reasons.push(reason),
+            }
+        }
+        Err(format!("none matched: {}", reasons.join("; ")))
+    }
+    fn is_async(&self) -> bool {
+        self.0.iter().any(|matcher| matcher.is_async())
+    }
+    fn check_async<'a>(&'a self, req: &'a Request) -> BoxFuture<'a, Result<(), String>> {
+        async move {
+            let mut reasons = Vec::new();
+            for matcher in &self.0 {
+                match matcher.check_async(req).await {
+                    Ok(()) => return Ok(()),
+                    Err(reason) => reasons.push(reason),
+                }
+            }
+            Err(format!("none matched: {}", reasons.join("; ")))
+        }
+        .boxed()
+    }
+}
+
+impl AnyOfMatcher {
+    pub(crate) fn new(matchers: Vec<Arc<dyn Matcher>>) -> Self {
+        assert!(!matchers.is_empty(), "any_of requires at least one matcher");
+        Self(sorted(matchers))
+    }
+}
+
+/// Matches a request that matches any of the matchers.
+///
+/// # Panics
+///
+/// Panics if `matchers` is empty, as it would never match.
+pub fn any_of(matchers: impl IntoIterator<Item = impl Matcher>) -> AnyOfMatcher {
+    AnyOfMatcher::new(boxed(matchers))
+}
+
+/// All of matcher.
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct AllOfMatcher(Vec<Arc<dyn Matcher>>);
+
+impl Matcher for AllOfMatcher {
+    fn name(&self) -> &str {
+        "all_of"
+    }
+    fn matches(&self, req: &Request) -> bool {
+        !self.is_async() && self.0.iter().all(|matcher| matcher.matches(req))
+    }
+    fn check(&self, req: &Request) -> Result<(), String> {
+        if self.is_async() {
+            return Err(format!("{} must be evaluated asynchronously", self.name()));
+        }
+        self.0.iter().try_for_each(|matcher| matcher.check(req))
+    }
+    fn is_async(&self) -> bool {
+        self.0.iter().any(|matcher| matcher.is_async())
+    }
+    fn check_async<'a>(&'a self, req: &'a Request) -> BoxFuture<'a, Result<(), String>> {
+        async move {
+            for matcher in &self.0 {
+                matcher.check_async(req).await?;
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+impl AllOfMatcher {
+    pub(crate) fn new(matchers: Vec<Arc<dyn Matcher>>) -> Self {
+        Self(sorted(matchers))
+    }
+}
+
+/// Matches a request that matches all of the matchers.
+///
+/// An empty set of matchers matches any request.
+pub fn all_of(matchers: impl IntoIterator<Item = impl Matcher>) -> AllOfMatcher {
+    AllOfMatcher::new(boxed(matchers))
+}
+
+/// Wraps matchers as trait objects.
+fn boxed(matchers: impl IntoIterator<Item = impl Matcher>) -> Vec<Arc<dyn Matcher>> {
+    matchers
+        .into_iter()
+        .map(|matcher| Arc::new(matcher) as Arc<dyn Matcher>)
+        .collect()
+}
+
+/// Sorts and deduplicates matchers.
+fn sorted(mut matchers: Vec<Arc<dyn Matcher>>) -> Vec<Arc<dyn Matcher>> {
+    matchers.sort_unstable();
+    matchers.dedup();
+    matchers
+}
+
+/// The outcome of a matcher function: a `bool` or a `Result` with a failure reason.
+pub trait MatchOutcome {
+    /// Converts into a result with an optional failure reason.
+    fn into_result(self) -> Result<(), Option<String>>;
+}
+
+impl MatchOutcome for bool {
+    fn into_result(self) -> Result<(), Option<String>> {
+        if self {
+            Ok(())
+        } else {
+            Err(None)
+        }
+    }
+}
+
+impl<E: ToString> MatchOutcome for Result<(), E> {
+    fn into_result(self) -> Result<(), Option<String>> {
+        self.map_err(|reason| Some(reason.to_string()))
+    }
+}
+
+type MatchFn = dyn Fn(&Request) -> Result<(), Option<String>> + Send + Sync;
+
+/// Function matcher.
+///
+/// Function matchers are equal only to themselves.
+#[derive(Clone)]
+pub struct FnMatcher {
+    name: String,
+    f: Arc<MatchFn>,
+}
+
+impl std::fmt::Debug for FnMatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FnMatcher")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for FnMatcher {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for FnMatcher {}
+
+impl PartialOrd for FnMatcher {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Ordered by name, then by function address.
+impl Ord for FnMatcher {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.name.cmp(&other.name).then_with(|| {
+            Arc::as_ptr(&self.f)
+                .cast::<()>()
+                .cmp(&Arc::as_ptr(&other.f).cast::<()>())
+        })
+    }
+}
+
+impl Matcher for FnMatcher {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn matches(&self, req: &Request) -> bool {
+        (self.f)(req).is_ok()
+    }
+    fn check(&self, req: &Request) -> Result<(), String> {
+        (self.f)(req)
+            .map_err(|reason| reason.unwrap_or_else(|| format!("{} did not match", self.name)))
+    }
+}
+
+/// Matches a request with a function returning a [`MatchOutcome`].
+pub fn from_fn<F, R>(name: impl Into<String>, f: F) -> FnMatcher
+where
+    F: Fn(&Request) -> R + Send + Sync + 'static,
+    R: MatchOutcome,
+{
+    FnMatcher {
+        name: name.into(),
+        f: Arc::new(move |req| f(req).into_result()),
+    }
+}
+
+type AsyncMatchFn = dyn Fn(Request) -> BoxFuture<'static, Result<(), Option<String>>> + Send + Sync;
+
+/// Async function matcher.
+///
+/// Async matchers are only evaluated by the mock server, see [`Matcher::is_async`].
+/// Function matchers are equal only to themselves.
+#[derive(Clone)]
+pub struct AsyncFnMatcher {
+    name: String,
+    f: Arc<AsyncMatchFn>,
+}
+
+impl std::fmt::Debug for AsyncFnMatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AsyncFnMatcher")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for AsyncFnMatcher {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for AsyncFnMatcher {}
+
+impl PartialOrd for AsyncFnMatcher {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Ordered by name, then by function address.
+impl Ord for AsyncFnMatcher {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.name.cmp(&other.name).then_with(|| {
+            Arc::as_ptr(&self.f)
+                .cast::<()>()
+                .cmp(&Arc::as_ptr(&other.f).cast::<()>())
+        })
+    }
+}
+
+impl Matcher for AsyncFnMatcher {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    /// Always `false`, async matchers are evaluated with [`Matcher::check_async`].
+    fn matches(&self, _req: &Request) -> bool {
+        false
+    }
+    fn check(&self, _req: &Request) -> Result<(), String> {
+        Err(format!("{} must be evaluated asynchronously", self.name))
+    }
+    fn is_async(&self) -> bool {
+        true
+    }
+    fn check_async<'a>(&'a self, req: &'a Request) -> BoxFuture<'a, Result<(), String>> {
+        let fut = (self.f)(req.clone());
+        async move {
+            fut.await
+                .map_err(|reason| reason.unwrap_or_else(|| format!("{} did not match", self.name)))
+        }
+        .boxed()
+    }
+}
+
+/// Matches a request with an async function returning a [`MatchOutcome`].
+pub fn from_async_fn<F, Fut, R>(name: impl Into<String>, f: F) -> AsyncFnMatcher
+where
+    F: Fn(Request) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = R> + Send + 'static,
+    R: MatchOutcome + 'static,
+{
+    AsyncFnMatcher {
+        name: name.into(),
+        f: Arc::new(move |req| f(req).map(MatchOutcome::into_result).boxed()),
+    }
+}
+
 #[doc(hidden)]
 pub trait AsMatcherEq {
     /// Returns matcher as [`&dyn MatcherEq`] to compare to another matcher.
@@ -275,6 +777,29 @@ impl PartialOrd<dyn MatcherEq> for dyn MatcherEq {
     }
 }
 
+impl dyn Matcher {
+    /// Returns the method of a method matcher.
+    pub(crate) fn as_method(&self) -> Option<&Method> {
+        self.as_matcher_eq()
+            .as_any()
+            .downcast_ref::<MethodMatcher>()
+            .map(|matcher| &matcher.0)
+    }
+
+    /// Returns `true` if the matcher is a bidi matcher.
+    pub(crate) fn is_bidi(&self) -> bool {
+        self.as_matcher_eq().as_any().is::<BidiMatcher>()
+    }
+
+    /// Returns the path of a path matcher.
+    pub(crate) fn as_path(&self) -> Option<&str> {
+        self.as_matcher_eq()
+            .as_any()
+            .downcast_ref::<PathMatcher>()
+            .map(|matcher| matcher.0.as_str())
+    }
+}
+
 impl PartialEq for dyn Matcher {
     fn eq(&self, other: &Self) -> bool {
         self.as_matcher_eq() == other.as_matcher_eq()
@@ -289,13 +814,13 @@ impl PartialOrd for dyn Matcher {
     }
 }
 
+/// Ordered by name, then by type, then by the order of matchers of the same type.
 impl Ord for dyn Matcher {
     fn cmp(&self, other: &Self) -> Ordering {
-        let compare = self.as_matcher_eq().m_partial_cmp(other.as_matcher_eq());
-        if let Some(ordering) = compare {
-            ordering
-        } else {
-            self.name().cmp(other.name())
-        }
+        let (this, other) = (self.as_matcher_eq(), other.as_matcher_eq());
+        this.name()
+            .cmp(other.name())
+            .then_with(|| this.as_any().type_id().cmp(&other.as_any().type_id()))
+            .then_with(|| this.m_partial_cmp(other).unwrap_or(Ordering::Equal))
     }
 }
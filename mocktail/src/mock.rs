@@ -4,6 +4,7 @@ use std::sync::{
     Arc,
 };
 
+use tracing::Level;
 use uuid::Uuid;
 
 use crate::{
@@ -14,6 +15,7 @@ use crate::{
 };
 
 const DEFAULT_PRIORITY: u8 = 5;
+const LIMIT_REACHED: &str = "limit reached";
 
 /// A mock.
 #[derive(Debug)]
@@ -30,6 +32,8 @@ pub struct Mock {
     pub match_count: AtomicUsize,
     /// Limit on how many times this mock can be matched.
     pub limit: Option<usize>,
+    /// Group name.
+    pub group: Option<String>,
 }
 
 impl Mock {
@@ -49,6 +53,7 @@ impl Mock {
             priority: DEFAULT_PRIORITY,
             match_count: AtomicUsize::new(0),
             limit: None,
+            group: None,
         }
     }
 
@@ -64,6 +69,12 @@ impl Mock {
         self
     }
 
+    /// Sets the mock group.
+    pub fn with_group(mut self, group: impl Into<String>) -> Self {
+        self.group = Some(group.into());
+        self
+    }
+
     /// Returns the mock ID.
     pub fn id(&self) -> &Uuid {
         &self.id
@@ -79,23 +90,97 @@ impl Mock {
         self.priority
     }
 
+    /// Returns the mock group.
+    pub fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+
+    /// Returns `true` if the mock matches the messages of a bidirectional stream individually.
+    pub fn is_bidi(&self) -> bool {
+        self.matchers.iter().any(|matcher| matcher.is_bidi())
+    }
+
     /// Returns the match count.
     pub fn match_count(&self) -> usize {
         self.match_count.load(Ordering::Relaxed)
     }
 
     /// Evaluates a request against match conditions.
+    ///
+    /// A match is counted atomically, so a limited mock is never matched more than its limit.
+    /// Async matchers never match, see [`Mock::matches_async`].
     pub fn matches(&self, req: &Request) -> bool {
-        if let Some(limit) = self.limit {
-            if self.match_count.load(Ordering::Relaxed) >= limit {
-                return false;
+        if self.is_exhausted() {
+            return false;
+        }
+        if !self.matchers.iter().all(|matcher| matcher.matches(req)) {
+            return false;
+        }
+        self.count_match()
+    }
+
+    /// Evaluates a request against match conditions, including async matchers,
+    /// returning the reason of the first unmatched condition.
+    ///
+    /// Sync matchers are evaluated first. A match is counted as in [`Mock::matches`].
+    /// Reasons of sync matchers are only built if debug logging is enabled.
+    pub async fn matches_async(&self, req: &Request) -> Result<(), String> {
+        if self.is_exhausted() {
+            return Err(LIMIT_REACHED.to_string());
+        }
+        for matcher in self.matchers.iter().filter(|matcher| !matcher.is_async()) {
+            if !matcher.matches(req) {
+                if !tracing::enabled!(Level::DEBUG) {
+                    return Err(String::new());
+                }
+                return Err(matcher.check(req).err().unwrap_or_default());
+            }
+        }
+        for matcher in self.matchers.iter().filter(|matcher| matcher.is_async()) {
+            matcher.check_async(req).await?;
+        }
+        if self.count_match() {
+            Ok(())
+        } else {
+            Err(LIMIT_REACHED.to_string())
+        }
+    }
+
+    /// Evaluates a request against match conditions without counting a match,
+    /// returning the failure reasons of all unmatched conditions.
+    pub async fn check(&self, req: &Request) -> Result<(), Vec<String>> {
+        let mut reasons = Vec::new();
+        if self.is_exhausted() {
+            reasons.push(LIMIT_REACHED.to_string());
+        }
+        for matcher in &self.matchers {
+            if let Err(reason) = matcher.check_async(req).await {
+                reasons.push(reason);
             }
         }
-        let matched = self.matchers.iter().all(|matcher| matcher.matches(req));
-        if matched {
-            self.match_count.fetch_add(1, Ordering::Relaxed);
+        if reasons.is_empty() {
+            Ok(())
+        } else {
+            Err(reasons)
         }
-        matched
+    }
+
+    /// Counts a match, unless the mock has reached its limit.
+    fn count_match(&self) -> bool {
+        self.match_count
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+                match self.limit {
+                    Some(limit) if count >= limit => None,
+                    _ => Some(count + 1),
+                }
+            })
+            .is_ok()
+    }
+
+    /// Returns `true` if the mock has reached its limit.
+    pub fn is_exhausted(&self) -> bool {
+        self.limit
+            .is_some_and(|limit| self.match_count.load(Ordering::Acquire) >= limit)
     }
 
     /// Resets the match counter.
@@ -112,6 +197,7 @@ impl PartialEq for Mock {
             && self.priority == other.priority
             && self.match_count.load(Ordering::Relaxed) == other.match_count.load(Ordering::Relaxed)
             && self.limit == other.limit
+            && self.group == other.group
     }
 }
 
@@ -124,6 +210,7 @@ impl Clone for Mock {
             priority: self.priority,
             match_count: AtomicUsize::new(self.match_count.load(Ordering::Relaxed)),
             limit: self.limit,
+            group: self.group.clone(),
         }
     }
 }
@@ -160,4 +247,30 @@ mod tests {
         assert!(mock.matches(&request));
         assert!(!mock.matches(&request));
     }
+    #[test]
+    fn test_limit_concurrent() {
+        let mock = Arc::new(
+            Mock::new(|when, then| {
+                when.get();
+                then.ok();
+            })
+            .with_limit(50),
+        );
+        let request = Request::new(Method::GET, "http://localhost/".parse().unwrap());
+        let matched = AtomicUsize::new(0);
+        std::thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    for _ in 0..100 {
+                        if mock.matches(&request) {
+                            matched.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                });
+            }
+        });
+        assert_eq!(matched.load(Ordering::Relaxed), 50);
+        assert_eq!(mock.match_count(), 50);
+        assert!(mock.is_exhausted());
+    }
 }
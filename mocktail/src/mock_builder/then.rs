@@ -1,34 +1,56 @@
 //! Then
-use std::{cell::Cell, rc::Rc};
+use std::{cell::Cell, rc::Rc, time::Duration};
 
+use base64::{engine::general_purpose::STANDARD_NO_PAD, Engine};
 use bytes::Bytes;
 
 use crate::{
     body::Body,
+    grpc::{CompressionEncoding, ErrorDetail},
     headers::{HeaderName, HeaderValue, Headers},
     response::Response,
-    status::StatusCode,
+    sse::SseEvent,
+    status::{Code, StatusCode},
+    websocket::WsScript,
 };
 
 /// A response builder.
 #[derive(Default, Clone)]
-pub struct Then(Rc<Cell<Response>>);
+pub struct Then {
+    response: Rc<Cell<Response>>,
+    /// Whether the body is a Server-Sent Events stream.
+    sse: Rc<Cell<bool>>,
+}
 
 impl Then {
     pub fn new() -> Self {
-        Self(Rc::new(Cell::new(Response::default())))
+        Self::default()
     }
 
     /// Returns the inner response.
+    ///
+    /// Server-Sent Events responses get default `content-type` and
+    /// `cache-control` headers, unless they are set.
     pub fn into_inner(self) -> Response {
-        self.0.take()
+        let mut response = self.response.take();
+        if self.sse.get() {
+            for (name, value) in [
+                ("content-type", "text/event-stream"),
+                ("cache-control", "no-cache"),
+            ] {
+                if response.headers.get(name).is_none() {
+                    response.headers.insert(name, value);
+                }
+            }
+        }
+        response
     }
 
     /// Updates the response.
     fn update<F: FnOnce(&mut Response)>(&self, f: F) {
-        let mut r = self.0.take();
+        let mut r = self.response.take();
         f(&mut r);
-        self.0.set(r);
+        self.response.set(r);
     }
 
     /// HTTP status code.
@@ -39,6 +61,16 @@ impl Then {
         self
     }
 
+    /// gRPC status code.
+    ///
+    /// Takes precedence over the gRPC status code mapped from the HTTP status code.
+    pub fn grpc_status(self, code: Code) -> Self {
+        self.update(|r| {
+            r.code = Some(code);
+        });
+        self
+    }
+
     /// HTTP headers.
     pub fn headers<T, U>(self, headers: impl IntoIterator<Item = (T, U)>) -> Self
     where
@@ -51,6 +83,98 @@ impl Then {
         self
     }
 
+    /// HTTP trailers, sent in a trailers frame after the body.
+    ///
+    /// Added to any trailers already set, like [`Then::grpc_trailers`].
+    pub fn trailers<T, U>(self, trailers: impl IntoIterator<Item = (T, U)>) -> Self
+    where
+        T: Into<HeaderName>,
+        U: Into<HeaderValue>,
+    {
+        self.update(|r| {
+            r.trailers.extend(trailers);
+        });
+        self
+    }
+
+    /// gRPC initial metadata, sent as response headers.
+    pub fn grpc_metadata<T, U>(self, metadata: impl IntoIterator<Item = (T, U)>) -> Self
+    where
+        T: Into<HeaderName>,
+        U: Into<HeaderValue>,
+    {
+        self.update(|r| {
+            r.headers.extend(metadata);
+        });
+        self
+    }
+
+    /// gRPC binary initial metadata, sent as base64 encoded response headers.
+    ///
+    /// The `-bin` suffix is appended to names without it.
+    pub fn grpc_metadata_bin(
+        self,
+        metadata: impl IntoIterator<Item = (impl Into<String>, impl AsRef<[u8]>)>,
+    ) -> Self {
+        self.grpc_metadata(
+            metadata
+                .into_iter()
+                .map(|(name, value)| bin_metadata(name, value)),
+        )
+    }
+
+    /// gRPC trailing metadata, sent in the trailers frame.
+    pub fn grpc_trailers<T, U>(self, metadata: impl IntoIterator<Item = (T, U)>) -> Self
+    where
+        T: Into<HeaderName>,
+        U: Into<HeaderValue>,
+    {
+        self.trailers(metadata)
+    }
+
+    /// gRPC binary trailing metadata, sent as base64 encoded values in the trailers frame.
+    ///
+    /// The `-bin` suffix is appended to names without it.
+    pub fn grpc_trailers_bin(
+        self,
+        metadata: impl IntoIterator<Item = (impl Into<String>, impl AsRef<[u8]>)>,
+    ) -> Self {
+        self.trailers(
+            metadata
+                .into_iter()
+                .map(|(name, value)| bin_metadata(name, value)),
+        )
+    }
+
+    /// gRPC message compression.
+    ///
+    /// Messages are only compressed if the client accepts the encoding.
+    pub fn grpc_compression(self, encoding: CompressionEncoding) -> Self {
+        self.update(|r| {
+            r.compression = Some(encoding);
+        });
+        self
+    }
+
+    /// Delay before sending the response body.
+    ///
+    /// For gRPC, initial metadata is sent first and a delay exceeding the
+    /// request deadline (`grpc-timeout`) results in `DeadlineExceeded`.
+    pub fn delay(self, delay: Duration) -> Self {
+        self.update(|r| {
+            r.delay = Some(delay);
+        });
+        self
+    }
+
+    /// WebSocket conversation, run once the upgrade is accepted.
+    pub fn websocket(self, script: WsScript) -> Self {
+        self.update(|r| {
+            r.websocket = Some(script);
+        });
+        self
+    }
+
     /// Body.
     pub fn body(self, body: Body) -> Self {
         self.update(|r| {
@@ -66,6 +190,14 @@ impl Then {
         });
         self
     }
+
+    /// gRPC rich error details, sent in the `grpc-status-details-bin` trailer.
+    pub fn grpc_error_details(self, details: impl IntoIterator<Item = ErrorDetail>) -> Self {
+        self.update(|r| {
+            r.error_details = details.into_iter().collect();
+        });
+        self
+    }
 }
 
 /// Body convenience methods.
@@ -136,6 +268,29 @@ impl Then {
         self
     }
 
+    /// Server-Sent Events streaming body.
+    pub fn sse(self, events: impl IntoIterator<Item = impl Into<SseEvent>>) -> Self {
+        self.sse.set(true);
+        self.update(|r| {
+            r.body = Body::sse(events);
+        });
+        self
+    }
+
+    /// Server-Sent Events streaming body with JSON data.
+    pub fn sse_json(self, messages: impl IntoIterator<Item = impl serde::Serialize>) -> Self {
+        self.sse(messages.into_iter().map(SseEvent::json))
+    }
+
+    /// Keeps a Server-Sent Events stream open after its events are sent,
+    /// sending keep-alive comments at an interval until the client disconnects.
+    pub fn sse_keep_alive(self, interval: Duration) -> Self {
+        self.update(|r| {
+            r.sse_keep_alive = Some(interval);
+        });
+        self
+    }
+
     /// Protobuf body.
     pub fn pb(self, body: impl prost::Message) -> Self {
         self.update(|r| {
@@ -264,3 +419,12 @@ impl Then {
         self
     }
 }
+
+/// Returns a binary metadata entry with a `-bin` suffixed name and base64 encoded value.
+fn bin_metadata(name: impl Into<String>, value: impl AsRef<[u8]>) -> (String, String) {
+    let mut name: String = name.into();
+    if !name.ends_with("-bin") {
+        name.push_str("-bin");
+    }
+    (name, STANDARD_NO_PAD.encode(value))
+}
@@ -1,5 +1,5 @@
 //! When
-use std::{cell::Cell, rc::Rc, sync::Arc};
+use std::{cell::Cell, future::Future, rc::Rc, sync::Arc, time::Duration};
 
 use bytes::Bytes;
 
@@ -7,8 +7,9 @@ use crate::{
     body::Body,
     headers::{HeaderName, HeaderValue, Headers},
     matchers,
-    matchers::Matcher,
-    request::Method,
+    matchers::{MatchOutcome, Matcher},
+    request::{Method, Request},
+    sse::SseEvent,
 };
 
 /// A request match conditions builder.
@@ -97,6 +98,12 @@ impl When {
         self
     }
 
+    /// Origin header.
+    pub fn origin(self, origin: impl Into<String>) -> Self {
+        self.push(matchers::origin(origin));
+        self
+    }
+
     /// Header exists.
     pub fn header_exists(self, name: impl Into<String>) -> Self {
         self.push(matchers::header_exists(name));
@@ -124,11 +131,92 @@ impl When {
         self
     }
 
+    /// gRPC deadline (`grpc-timeout`) is set and at most `timeout`.
+    pub fn grpc_timeout_at_most(self, timeout: Duration) -> Self {
+        self.push(matchers::grpc_timeout_at_most(timeout));
+        self
+    }
+
+    /// Bidirectional gRPC stream: each request message is matched individually,
+    /// and the response is sent right away.
+    pub fn bidi(self) -> Self {
+        self.push(matchers::bidi());
+        self
+    }
+
+    /// WebSocket upgrade, over HTTP/1.1 or HTTP/2.
+    pub fn websocket(self) -> Self {
+        self.push(matchers::websocket());
+        self
+    }
+
     /// Custom matcher.
     pub fn matcher(self, matcher: impl Matcher) -> Self {
         self.push(matcher);
         self
     }
+
+    /// Negation of the matchers built by `f`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `f` builds no matchers, as the negation would never match.
+    pub fn not<F>(self, f: F) -> Self
+    where
+        F: FnOnce(When) -> When,
+    {
+        let mut matchers = f(When::new()).into_inner();
+        assert!(!matchers.is_empty(), "not requires at least one matcher");
+        if matchers.len() == 1 {
+            self.push(matchers::NotMatcher::new(matchers.remove(0)));
+        } else {
+            self.push(matchers::not(matchers::AllOfMatcher::new(matchers)));
+        }
+        self
+    }
+
+    /// Any of the matchers built by `f`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `f` builds no matchers, as it would never match.
+    pub fn any_of<F>(self, f: F) -> Self
+    where
+        F: FnOnce(When) -> When,
+    {
+        self.push(matchers::AnyOfMatcher::new(f(When::new()).into_inner()));
+        self
+    }
+
+    /// All of the matchers built by `f`, to group matchers within [`When::any_of`].
+    pub fn all_of<F>(self, f: F) -> Self
+    where
+        F: FnOnce(When) -> When,
+    {
+        self.push(matchers::AllOfMatcher::new(f(When::new()).into_inner()));
+        self
+    }
+
+    /// Custom matcher function, see [`matchers::from_fn`].
+    pub fn matches_fn<F, R>(self, name: impl Into<String>, f: F) -> Self
+    where
+        F: Fn(&Request) -> R + Send + Sync + 'static,
+        R: MatchOutcome,
+    {
+        self.push(matchers::from_fn(name, f));
+        self
+    }
+
+    /// Custom async matcher function, see [`matchers::from_async_fn`].
+    pub fn matches_async_fn<F, Fut, R>(self, name: impl Into<String>, f: F) -> Self
+    where
+        F: Fn(Request) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = R> + Send + 'static,
+        R: MatchOutcome + 'static,
+    {
+        self.push(matchers::from_async_fn(name, f));
+        self
+    }
 }
 
 /// Body convenience methods.
@@ -183,6 +271,12 @@ impl When {
         self
     }
 
+    /// Server-Sent Events body.
+    pub fn sse(self, events: impl IntoIterator<Item = impl Into<SseEvent>>) -> Self {
+        self.push(matchers::sse(events));
+        self
+    }
+
     /// Protobuf body.
     pub fn pb(self, body: impl prost::Message) -> Self {
         self.push(matchers::body(Body::pb(body)));
@@ -194,6 +288,19 @@ impl When {
         self.push(matchers::body(Body::pb_stream(messages)));
         self
     }
+
+    /// Protobuf gRPC request message at index, from the start of the request stream.
+    pub fn pb_message(self, index: usize, message: impl prost::Message) -> Self {
+        self.push(matchers::message(index, message.encode_to_vec()));
+        self
+    }
+
+    /// Protobuf gRPC request messages.
+    pub fn pb_messages(self, messages: impl IntoIterator<Item = impl prost::Message>) -> Self {
+        let messages = messages.into_iter().map(|msg| msg.encode_to_vec());
+        self.push(matchers::messages(messages));
+        self
+    }
 }
 
 /// Method convenience methods.
@@ -227,4 +334,16 @@ impl When {
         self.push(matchers::method(Method::HEAD));
         self
     }
+
+    /// HTTP PATCH method.
+    pub fn patch(self) -> Self {
+        self.push(matchers::method(Method::PATCH));
+        self
+    }
+
+    /// HTTP OPTIONS method.
+    pub fn options(self) -> Self {
+        self.push(matchers::method(Method::OPTIONS));
+        self
+    }
 }
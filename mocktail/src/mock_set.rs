@@ -1,13 +1,39 @@
 //! Mock set
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    sync::Arc,
+};
+
+use uuid::Uuid;
+
 use crate::{
     mock::Mock,
     mock_builder::{Then, When},
-    request::Request,
+    request::{Method, Request},
 };
 
+/// Sort key of a mock: priority, then insertion order.
+type SortKey = (u8, u64);
+
+/// Index key of a mock: its method and exact path, if it has matchers for them.
+type IndexKey = (Option<Method>, Option<String>);
+
 /// A set of mocks.
+///
+/// Mocks are indexed by method and exact path, so matching only evaluates
+/// candidate mocks. Mocks without method or exact path matchers are
+/// candidates for every request.
 #[derive(Default, Debug, Clone)]
-pub struct MockSet(Vec<Mock>);
+pub struct MockSet {
+    /// Mocks sorted by [`SortKey`].
+    mocks: Vec<Arc<Mock>>,
+    /// Insertion order of mocks, aligned with `mocks`.
+    seqs: Vec<u64>,
+    next_seq: u64,
+    ids: HashSet<Uuid>,
+    index: HashMap<IndexKey, BTreeSet<SortKey>>,
+    disabled_groups: HashSet<String>,
+}
 
 impl MockSet {
     /// Creates an empty mockset.
@@ -17,25 +43,34 @@ impl MockSet {
 
     /// Returns the number of mocks.
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.mocks.len()
     }
 
     /// Returns true if empty.
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.mocks.is_empty()
     }
 
     /// Inserts a mock.
+    ///
+    /// Mocks are kept sorted by priority; mocks with equal priority
+    /// are matched in insertion order.
     pub fn insert(&mut self, mock: Mock) {
-        if !self.contains(&mock) {
-            self.0.push(mock);
-            self.0.sort_by_key(|mock| mock.priority());
+        if self.contains(&mock) {
+            return;
         }
+        let key = (mock.priority(), self.next_seq);
+        self.next_seq += 1;
+        let index = self.position(key).unwrap_or_else(|index| index);
+        self.ids.insert(mock.id);
+        self.index.entry(index_key(&mock)).or_default().insert(key);
+        self.mocks.insert(index, Arc::new(mock));
+        self.seqs.insert(index, key.1);
     }
 
-    // Returns `true` if the mockset contains the mock.
+    /// Returns `true` if the mockset contains a mock with the same ID.
     pub fn contains(&self, mock: &Mock) -> bool {
-        self.0.contains(mock)
+        self.ids.contains(&mock.id)
     }
 
     /// Builds and inserts a mock with default options.
@@ -59,48 +94,197 @@ impl MockSet {
         self.insert(mock);
     }
 
+    /// Builds and inserts a mock in a group.
+    pub fn mock_in_group<F>(&mut self, group: impl Into<String>, f: F)
+    where
+        F: FnOnce(When, Then),
+    {
+        let mock = Mock::new(f).with_group(group);
+        self.insert(mock);
+    }
+
+    /// Disables the mocks of a group, so they are not matched.
+    pub fn disable_group(&mut self, group: &str) {
+        self.disabled_groups.insert(group.to_string());
+    }
+
+    /// Enables the mocks of a group.
+    pub fn enable_group(&mut self, group: &str) {
+        self.disabled_groups.remove(group);
+    }
+
+    /// Returns `true` if the mocks of a group are enabled.
+    pub fn is_group_enabled(&self, group: &str) -> bool {
+        !self.disabled_groups.contains(group)
+    }
+
+    /// Resets the match counters of the mocks of a group.
+    pub fn reset_group(&self, group: &str) {
+        self.mocks
+            .iter()
+            .filter(|mock| mock.group() == Some(group))
+            .for_each(|mock| mock.reset());
+    }
+
+    /// Removes the mocks of a group, returning the number of mocks removed.
+    pub fn remove_group(&mut self, group: &str) -> usize {
+        let len = self.mocks.len();
+        let (mocks, seqs) = std::mem::take(&mut self.mocks)
+            .into_iter()
+            .zip(std::mem::take(&mut self.seqs))
+            .filter(|(mock, _)| mock.group() != Some(group))
+            .unzip();
+        self.mocks = mocks;
+        self.seqs = seqs;
+        self.rebuild_index();
+        self.disabled_groups.remove(group);
+        len - self.mocks.len()
+    }
+
     /// Finds a mock by predicate.
-    pub fn find<P>(&self, predicate: P) -> Option<&Mock>
+    pub fn find<P>(&self, predicate: P) -> Option<&Arc<Mock>>
     where
-        P: FnMut(&&Mock) -> bool,
+        P: FnMut(&&Arc<Mock>) -> bool,
     {
-        self.0.iter().find(predicate)
+        self.mocks.iter().find(predicate)
     }
 
     /// Removes a mock by index.
-    pub fn remove(&mut self, index: usize) -> Mock {
-        self.0.remove(index)
+    pub fn remove(&mut self, index: usize) -> Arc<Mock> {
+        let mock = self.mocks.remove(index);
+        let seq = self.seqs.remove(index);
+        self.ids.remove(&mock.id);
+        let index_key = index_key(&mock);
+        if let Some(keys) = self.index.get_mut(&index_key) {
+            keys.remove(&(mock.priority(), seq));
+            if keys.is_empty() {
+                self.index.remove(&index_key);
+            }
+        }
+        mock
+    }
+
+    /// Removes a mock by ID.
+    pub fn remove_by_id(&mut self, id: &Uuid) -> Option<Arc<Mock>> {
+        if !self.ids.contains(id) {
+            return None;
+        }
+        let index = self.mocks.iter().position(|mock| mock.id() == id)?;
+        Some(self.remove(index))
     }
 
     /// Clears the mockset.
     pub fn clear(&mut self) {
-        self.0.clear()
+        self.mocks.clear();
+        self.seqs.clear();
+        self.ids.clear();
+        self.index.clear();
     }
 
     /// Returns an iterator over the mockset.
-    pub fn iter(&self) -> std::slice::Iter<'_, Mock> {
-        self.0.iter()
+    pub fn iter(&self) -> std::slice::Iter<'_, Arc<Mock>> {
+        self.mocks.iter()
     }
 
     /// Matches a request to a mock.
-    pub fn match_by_request(&self, request: &Request) -> Option<Mock> {
-        self.0.iter().find(|&mock| mock.matches(request)).cloned()
+    ///
+    /// Candidate mocks are evaluated in priority order. Mocks of disabled groups are skipped.
+    /// Mocks with async matchers never match, see [`MockSet::candidates`].
+    pub fn match_by_request(&self, request: &Request) -> Option<Arc<Mock>> {
+        self.candidates(request)
+            .into_iter()
+            .find(|mock| mock.matches(request))
     }
+
+    /// Returns the candidate mocks for a request in priority order, for evaluation
+    /// with [`Mock::matches_async`]. Mocks of disabled groups are skipped.
+    pub fn candidates(&self, request: &Request) -> Vec<Arc<Mock>> {
+        let path = request.path();
+        let method = Some(request.method.clone());
+        let candidate_keys = [
+            (method.clone(), Some(path)),
+            (None, Some(path)),
+            (method, None),
+            (None, None),
+        ];
+        let mut candidates = Vec::new();
+        for (method, path) in candidate_keys {
+            if let Some(keys) = self.index.get(&(method, path.map(String::from))) {
+                candidates.extend(keys.iter().copied());
+            }
+        }
+        candidates.sort_unstable();
+        candidates
+            .into_iter()
+            .filter_map(|key| self.position(key).ok().map(|index| &self.mocks[index]))
+            .filter(|mock| {
+                mock.group()
+                    .is_none_or(|group| !self.disabled_groups.contains(group))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the position of a mock by sort key.
+    fn position(&self, key: SortKey) -> Result<usize, usize> {
+        let mut lo = 0;
+        let mut hi = self.mocks.len();
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            match (self.mocks[mid].priority(), self.seqs[mid]).cmp(&key) {
+                std::cmp::Ordering::Less => lo = mid + 1,
+                std::cmp::Ordering::Greater => hi = mid,
+                std::cmp::Ordering::Equal => return Ok(mid),
+            }
+        }
+        Err(lo)
+    }
+
+    /// Rebuilds the ID set and index.
+    fn rebuild_index(&mut self) {
+        self.ids = self.mocks.iter().map(|mock| mock.id).collect();
+        self.index.clear();
+        for (mock, &seq) in self.mocks.iter().zip(&self.seqs) {
+            self.index
+                .entry(index_key(mock))
+                .or_default()
+                .insert((mock.priority(), seq));
+        }
+    }
+}
+
+/// Returns the index key of a mock.
+fn index_key(mock: &Mock) -> IndexKey {
+    let method = mock
+        .matchers
+        .iter()
+        .find_map(|matcher| matcher.as_method())
+        .cloned();
+    let path = mock
+        .matchers
+        .iter()
+        .find_map(|matcher| matcher.as_path())
+        .map(String::from);
+    (method, path)
 }
 
 impl IntoIterator for MockSet {
-    type Item = Mock;
+    type Item = Arc<Mock>;
 
     type IntoIter = std::vec::IntoIter<Self::Item>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.0.into_iter()
+        self.mocks.into_iter()
     }
 }
 
 impl FromIterator<Mock> for MockSet {
     fn from_iter<I: IntoIterator<Item = Mock>>(iter: I) -> Self {
-        Self(iter.into_iter().collect())
+        let mut mocks = Self::default();
+        for mock in iter {
+            mocks.insert(mock);
+        }
+        mocks
     }
 }
 
@@ -121,4 +305,72 @@ mod test {
         });
         assert_eq!(mocks.len(), 2);
     }
+    #[test]
+    fn test_remove_by_id() {
+        let mut mocks = MockSet::new();
+        let mock = Mock::new(|when, then| {
+            when.get().path("/hello");
+            then.text("hello!");
+        })
+        .with_priority(1);
+        let id = *mock.id();
+        mocks.mock(|when, then| {
+            when.get().path("/world");
+            then.text("world!");
+        });
+        mocks.insert(mock);
+        assert!(mocks.remove_by_id(&id).is_some_and(|mock| mock.id() == &id));
+        assert!(mocks.remove_by_id(&id).is_none());
+        assert_eq!(mocks.len(), 1);
+    }
+    #[test]
+    fn test_match_by_request_priority() {
+        let mut mocks = MockSet::new();
+        mocks.mock_with_options(3, None, |when, then| {
+            when.get().path("/hello");
+            then.text("exact");
+        });
+        mocks.mock_with_options(1, None, |when, then| {
+            when.path_prefix("/hel");
+            then.text("prefix");
+        });
+        mocks.mock_with_options(3, None, |when, then| {
+            when.get();
+            then.text("method");
+        });
+        mocks.mock_with_options(2, None, |when, then| {
+            when.path("/hello");
+            then.text("path");
+        });
+
+        let request = |method, path: &str| {
+            Request::new(method, format!("http://localhost{path}").parse().unwrap())
+        };
+        let body = |mocks: &MockSet, request: &Request| {
+            mocks
+                .match_by_request(request)
+                .map(|mock| mock.response().body().clone().as_bytes())
+        };
+        // Priority is kept across indexed and unindexed mocks
+        assert_eq!(
+            body(&mocks, &request(Method::GET, "/hello")),
+            Some("prefix".into())
+        );
+        mocks.remove(0);
+        assert_eq!(
+            body(&mocks, &request(Method::GET, "/hello")),
+            Some("path".into())
+        );
+        mocks.remove(0);
+        // Insertion order is kept for equal priority
+        assert_eq!(
+            body(&mocks, &request(Method::GET, "/hello")),
+            Some("exact".into())
+        );
+        assert_eq!(
+            body(&mocks, &request(Method::GET, "/world")),
+            Some("method".into())
+        );
+        assert_eq!(body(&mocks, &request(Method::POST, "/hello")), None);
+    }
 }
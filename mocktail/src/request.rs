@@ -1,4 +1,5 @@
 //! Mock request
+use bytes::Bytes;
 use url::Url;
 
 use crate::{body::Body, headers::Headers};
@@ -10,6 +11,10 @@ pub struct Request {
     pub url: Url,
     pub headers: Headers,
     pub body: Body,
+    /// Decoded gRPC messages.
+    pub messages: Vec<Bytes>,
+    /// Extended CONNECT protocol (RFC 8441).
+    pub protocol: Option<String>,
 }
 
 impl Request {
@@ -19,6 +24,8 @@ impl Request {
             url,
             headers: Headers::default(),
             body: Body::default(),
+            messages: Vec::new(),
+            protocol: None,
         }
     }
 
@@ -30,11 +37,17 @@ impl Request {
         }
         .parse()
         .unwrap();
+        let protocol = parts
+            .extensions
+            .get::<hyper::ext::Protocol>()
+            .map(|protocol| protocol.as_str().to_string());
         Self {
             method: parts.method.into(),
             url,
             headers: parts.headers.into(),
             body: Body::default(),
+            messages: Vec::new(),
+            protocol,
         }
     }
 
@@ -48,6 +61,11 @@ impl Request {
         self
     }
 
+    pub fn with_messages(mut self, messages: Vec<Bytes>) -> Self {
+        self.messages = messages;
+        self
+    }
+
     pub fn method(&self) -> &Method {
         &self.method
     }
@@ -75,11 +93,28 @@ impl Request {
     pub fn body(&self) -> &Body {
         &self.body
     }
+
+    /// Returns true if the request is a WebSocket upgrade,
+    /// over HTTP/1.1 or HTTP/2 (RFC 8441).
+    pub fn is_websocket(&self) -> bool {
+        match self.method {
+            Method::CONNECT => self.protocol.as_deref() == Some("websocket"),
+            _ => self
+                .headers
+                .get("upgrade")
+                .is_some_and(|value| value.as_ref().eq_ignore_ascii_case("websocket")),
+        }
+    }
+
+    /// Returns the decoded gRPC messages.
+    pub fn messages(&self) -> &[Bytes] {
+        &self.messages
+    }
 }
 
 /// Represents a HTTP method.
 #[allow(clippy::upper_case_acronyms)]
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Method {
     #[default]
     GET,
@@ -91,30 +126,43 @@ pub enum Method {
     OPTIONS,
     TRACE,
     PATCH,
+    /// An extension method, e.g. `PROPFIND` or `PURGE`.
+    Other(String),
+}
+
+impl Method {
+    /// Returns the method as a string.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Method::GET => "GET",
+            Method::HEAD => "HEAD",
+            Method::POST => "POST",
+            Method::PUT => "PUT",
+            Method::DELETE => "DELETE",
+            Method::CONNECT => "CONNECT",
+            Method::OPTIONS => "OPTIONS",
+            Method::TRACE => "TRACE",
+            Method::PATCH => "PATCH",
+            Method::Other(method) => method,
+        }
+    }
 }
 
 impl std::fmt::Display for Method {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        std::fmt::Debug::fmt(self, f)
+        f.write_str(self.as_str())
     }
 }
 
+/// Parses a method token. Method tokens are case-sensitive, so e.g. `get`
+/// is an extension method rather than `GET`.
 impl std::str::FromStr for Method {
     type Err = String;
 
     fn from_str(value: &str) -> Result<Self, Self::Err> {
-        match value.to_uppercase().as_str() {
-            "GET" => Ok(Method::GET),
-            "HEAD" => Ok(Method::HEAD),
-            "POST" => Ok(Method::POST),
-            "PUT" => Ok(Method::PUT),
-            "DELETE" => Ok(Method::DELETE),
-            "CONNECT" => Ok(Method::CONNECT),
-            "OPTIONS" => Ok(Method::OPTIONS),
-            "TRACE" => Ok(Method::TRACE),
-            "PATCH" => Ok(Method::PATCH),
-            _ => Err(format!("Invalid HTTP method {value}")),
-        }
+        http::Method::from_bytes(value.as_bytes())
+            .map(Method::from)
+            .map_err(|_| format!("Invalid HTTP method {value}"))
     }
 }
 
@@ -122,18 +170,7 @@ impl TryFrom<&str> for Method {
     type Error = String;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match value {
-            "POST" => Ok(Self::POST),
-            "GET" => Ok(Self::GET),
-            "HEAD" => Ok(Self::HEAD),
-            "PUT" => Ok(Self::PUT),
-            "DELETE" => Ok(Self::DELETE),
-            "CONNECT" => Ok(Self::CONNECT),
-            "OPTIONS" => Ok(Self::OPTIONS),
-            "TRACE" => Ok(Self::TRACE),
-            "PATCH" => Ok(Self::PATCH),
-            _ => Err(format!("Invalid HTTP method {value}")),
-        }
+        value.parse()
     }
 }
 
@@ -149,7 +186,26 @@ impl From<http::Method> for Method {
             "OPTIONS" => Self::OPTIONS,
             "TRACE" => Self::TRACE,
             "PATCH" => Self::PATCH,
-            _ => unimplemented!(),
+            method => Self::Other(method.to_string()),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_method_parse() {
+        assert_eq!("POST".parse::<Method>(), Ok(Method::POST));
+        assert_eq!(Method::try_from("POST"), Ok(Method::POST));
+        // Method tokens are case-sensitive
+        assert_eq!(Method::try_from("post"), Ok(Method::Other("post".into())));
+        assert_eq!("post".parse::<Method>(), Ok(Method::Other("post".into())));
+        assert_eq!(
+            Method::try_from("PROPFIND"),
+            Ok(Method::Other("PROPFIND".into()))
+        );
+        assert!(Method::try_from("bad method").is_err());
+    }
+}
@@ -1,22 +1,44 @@
 //! Mock response
-use super::{body::Body, headers::Headers, status::StatusCode};
+use std::time::Duration;
+
+use super::{
+    body::Body,
+    grpc::{CompressionEncoding, ErrorDetail},
+    headers::Headers,
+    status::{Code, StatusCode},
+    websocket::WsScript,
+};
 
 /// Represents a HTTP response.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Response {
     pub status: StatusCode,
+    pub code: Option<Code>,
     pub headers: Headers,
+    pub trailers: Headers,
     pub body: Body,
     pub message: Option<String>,
+    pub error_details: Vec<ErrorDetail>,
+    pub compression: Option<CompressionEncoding>,
+    pub delay: Option<Duration>,
+    pub sse_keep_alive: Option<Duration>,
+    pub websocket: Option<WsScript>,
 }
 
 impl Response {
     pub fn new(body: impl Into<Body>) -> Self {
         Self {
             status: StatusCode::default(),
+            code: None,
             headers: Headers::default(),
+            trailers: Headers::default(),
             body: body.into(),
             message: None,
+            error_details: Vec::new(),
+            compression: None,
+            delay: None,
+            sse_keep_alive: None,
+            websocket: None,
         }
     }
 
@@ -25,24 +47,70 @@ impl Response {
         self
     }
 
+    pub fn with_code(mut self, code: Code) -> Self {
+        self.code = Some(code);
+        self
+    }
+
     pub fn with_headers(mut self, headers: Headers) -> Self {
         self.headers = headers;
         self
     }
 
+    pub fn with_trailers(mut self, trailers: Headers) -> Self {
+        self.trailers = trailers;
+        self
+    }
+
     pub fn with_message(mut self, message: impl Into<String>) -> Self {
         self.message = Some(message.into());
         self
     }
 
+    pub fn with_error_details(mut self, details: impl IntoIterator<Item = ErrorDetail>) -> Self {
+        self.error_details = details.into_iter().collect();
+        self
+    }
+
+    pub fn with_compression(mut self, encoding: CompressionEncoding) -> Self {
+        self.compression = Some(encoding);
+        self
+    }
+
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    pub fn with_sse_keep_alive(mut self, interval: Duration) -> Self {
+        self.sse_keep_alive = Some(interval);
+        self
+    }
+
+    pub fn with_websocket(mut self, script: WsScript) -> Self {
+        self.websocket = Some(script);
+        self
+    }
+
     pub fn status(&self) -> &StatusCode {
         &self.status
     }
 
+    /// Returns the gRPC status code.
+    ///
+    /// An explicitly set code takes precedence over the code mapped from the HTTP status.
+    pub fn code(&self) -> Code {
+        self.code.unwrap_or_else(|| self.status.as_grpc())
+    }
+
     pub fn headers(&self) -> &Headers {
         &self.headers
     }
 
+    pub fn trailers(&self) -> &Headers {
+        &self.trailers
+    }
+
     pub fn body(&self) -> &Body {
         &self.body
     }
@@ -51,6 +119,26 @@ impl Response {
         self.message.as_deref()
     }
 
+    pub fn error_details(&self) -> &[ErrorDetail] {
+        &self.error_details
+    }
+
+    pub fn compression(&self) -> Option<CompressionEncoding> {
+        self.compression
+    }
+
+    pub fn delay(&self) -> Option<Duration> {
+        self.delay
+    }
+
+    pub fn sse_keep_alive(&self) -> Option<Duration> {
+        self.sse_keep_alive
+    }
+
+    pub fn websocket(&self) -> Option<&WsScript> {
+        self.websocket.as_ref()
+    }
+
     pub fn is_ok(&self) -> bool {
         self.status.is_ok()
     }
@@ -64,9 +152,16 @@ impl Default for Response {
     fn default() -> Self {
         Self {
             status: StatusCode::OK,
+            code: None,
             headers: Headers::default(),
+            trailers: Headers::default(),
             body: Body::default(),
             message: None,
+            error_details: Vec::new(),
+            compression: None,
+            delay: None,
+            sse_keep_alive: None,
+            websocket: None,
         }
     }
 }
@@ -1,7 +1,10 @@
 //! Mock server
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
 use std::{
-    net::{IpAddr, Ipv4Addr, SocketAddr, TcpStream},
-    sync::{Arc, OnceLock, RwLock, RwLockReadGuard, RwLockWriteGuard},
+    collections::HashSet,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::{Arc, Mutex, OnceLock, RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError},
     time::Duration,
 };
 
@@ -9,22 +12,37 @@ use http_body::Body;
 use hyper::{body::Incoming, service::Service};
 use hyper_util::{
     rt::{TokioExecutor, TokioIo},
-    server::conn,
+    server::{conn, graceful::GracefulShutdown},
+};
+use tokio::{
+    io::DuplexStream,
+    net::TcpListener,
+    sync::{mpsc, oneshot},
+    task::{JoinHandle, JoinSet},
 };
-use rand::rngs::SmallRng;
-use rand::{Rng, SeedableRng};
-use tokio::net::TcpListener;
 use tracing::{debug, error, info};
 use url::Url;
+use uuid::Uuid;
 
 use crate::{
+    cors::Cors,
+    grpc::health::{HealthState, ServingStatus},
     mock::Mock,
     mock_builder::{Then, When},
     mock_set::MockSet,
+    request::Request,
     service::{GrpcMockService, HttpMockService},
     Error,
 };
 
+mod guard;
+pub use guard::MockGuard;
+mod handle;
+pub use handle::MockServerHandle;
+mod transport;
+pub use transport::MockChannel;
+use transport::{InMemory, Io, Listener, Transport};
+
 /// A mock server.
 pub struct MockServer {
     name: &'static str,
@@ -33,6 +51,10 @@ pub struct MockServer {
     base_url: OnceLock<Url>,
     state: Arc<MockServerState>,
     config: MockServerConfig,
+    running: Mutex<Option<RunningServer>>,
+    listener: Mutex<Option<std::net::TcpListener>>,
+    transport: Transport,
+    in_memory: InMemory,
 }
 
 impl MockServer {
@@ -45,6 +67,10 @@ impl MockServer {
             base_url: OnceLock::new(),
             state: Arc::new(MockServerState::default()),
             config: MockServerConfig::default(),
+            running: Mutex::new(None),
+            listener: Mutex::new(None),
+            transport: Transport::default(),
+            in_memory: InMemory::default(),
         }
     }
 
@@ -57,6 +83,10 @@ impl MockServer {
             base_url: OnceLock::new(),
             state: Arc::new(MockServerState::default()),
             config: MockServerConfig::default(),
+            running: Mutex::new(None),
+            listener: Mutex::new(None),
+            transport: Transport::default(),
+            in_memory: InMemory::default(),
         }
     }
 
@@ -69,6 +99,10 @@ impl MockServer {
             base_url: OnceLock::new(),
             state: Arc::new(MockServerState::default()),
             config: MockServerConfig::default(),
+            running: Mutex::new(None),
+            listener: Mutex::new(None),
+            transport: Transport::default(),
+            in_memory: InMemory::default(),
         }
     }
 
@@ -81,7 +115,7 @@ impl MockServer {
 
     /// Sets the server mocks.
     pub fn with_mocks(self, mocks: MockSet) -> Self {
-        *self.state.mocks.write().unwrap() = mocks;
+        *self.state.mocks_mut() = mocks;
         self
     }
 
@@ -91,62 +125,188 @@ impl MockServer {
         self
     }
 
+    /// Sets a bound listener to serve on, instead of binding to the configured address.
+    pub fn with_listener(self, listener: std::net::TcpListener) -> Self {
+        *self.listener.lock().unwrap() = Some(listener);
+        self
+    }
+
+    /// Listens on a Unix domain socket instead of TCP.
+    ///
+    /// The socket file is removed when the server stops.
+    #[cfg(unix)]
+    pub fn with_unix_socket(mut self, path: impl Into<PathBuf>) -> Self {
+        self.transport = Transport::Unix(path.into());
+        self
+    }
+
+    /// Serves in-memory connections only, without binding a socket.
+    ///
+    /// Use [`MockServer::channel`] to connect.
+    pub fn with_in_memory(mut self) -> Self {
+        self.transport = Transport::InMemory;
+        self
+    }
+
+    /// Starts the server.
+    ///
+    /// A stopped server is started on the same address.
     pub async fn start(&self) -> Result<(), Error> {
-        if self.addr().is_some() {
+        if self.is_running() {
             return Err(Error::ServerError("already running".into()));
         }
 
-        let mut counter = 0;
-        let mut rng = SmallRng::from_os_rng();
-
-        let listener = loop {
-            let port: u16 =
-                rng.random_range(self.config.port_range_start..self.config.port_range_end);
-            let addr = SocketAddr::from((self.config.listen_addr, port));
-            if let Ok(listener) = TcpListener::bind(&addr).await {
-                break listener;
-            }
-
-            if counter == self.config.bind_max_retries {
-                return Err(Error::ServerError("server failed to bind to port".into()));
-            }
-            counter += 1;
+        let listener = self.bind().await?;
+        info!(
+            "started {} [{}] server on {listener}",
+            self.name(),
+            &self.kind
+        );
+        let addr = listener.local_addr();
+        let endpoint = listener.endpoint();
+        let base_url = match addr {
+            Some(addr) => Url::parse(&format!("http://{addr}")).unwrap(),
+            None => Url::parse("http://localhost").unwrap(),
         };
-
-        let addr = listener.local_addr()?;
-        info!("started {} [{}] server on {addr}", self.name(), &self.kind);
-        let base_url = Url::parse(&format!("http://{}", &addr)).unwrap();
-
-        match self.kind {
+        let in_memory_rx = self.in_memory.listen();
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+        let shutdown_timeout = self.config.shutdown_timeout;
+        let task = match self.kind {
             ServerKind::Http => {
                 let service = HttpMockService::new(self.state.clone());
-                tokio::spawn(run_server(listener, self.kind, service));
+                tokio::spawn(run_server(
+                    listener,
+                    in_memory_rx,
+                    service,
+                    shutdown_rx,
+                    shutdown_timeout,
+                ))
             }
             ServerKind::Grpc => {
                 let service = GrpcMockService::new(self.state.clone());
-                tokio::spawn(run_server(listener, self.kind, service));
+                tokio::spawn(run_server(
+                    listener,
+                    in_memory_rx,
+                    service,
+                    shutdown_rx,
+                    shutdown_timeout,
+                ))
             }
         };
-        // Wait for server to become ready
+
+        // Wait for server to accept connections
         let mut counter = 0;
         loop {
-            if TcpStream::connect_timeout(&addr, self.config.ready_connect_timeout).is_ok() {
+            if endpoint.is_ready(self.config.ready_connect_timeout).await {
                 break;
             }
             if counter == self.config.ready_connect_max_retries {
+                let _ = shutdown_tx.send(());
+                task.abort();
                 return Err(Error::ServerError("server failed to become ready".into()));
             }
             counter += 1;
             tokio::time::sleep(Duration::from_millis(10)).await;
         }
+        *self.running.lock().unwrap() = Some(RunningServer { shutdown_tx, task });
         info!("{} server ready", self.name());
 
-        self.addr.set(addr).unwrap();
-        self.base_url.set(base_url).unwrap();
+        if let Some(addr) = addr {
+            let _ = self.addr.set(addr);
+        }
+        let _ = self.base_url.set(base_url);
+
+        Ok(())
+    }
+
+    /// Binds a listener for the configured transport.
+    async fn bind(&self) -> Result<Listener, Error> {
+        match &self.transport {
+            Transport::Tcp => {
+                let listener = self.listener.lock().unwrap().take();
+                let listener = match listener {
+                    Some(listener) => {
+                        listener.set_nonblocking(true)?;
+                        TcpListener::from_std(listener)?
+                    }
+                    None => {
+                        let mut addrs = self.bind_addrs().into_iter().peekable();
+                        loop {
+                            let addr = addrs.next().unwrap();
+                            match TcpListener::bind(addr).await {
+                                Ok(listener) => break listener,
+                                Err(err) if addrs.peek().is_none() => {
+                                    return Err(Error::ServerError(format!(
+                                        "server failed to bind to {addr}: {err}"
+                                    )))
+                                }
+                                Err(_) => continue,
+                            }
+                        }
+                    }
+                };
+                Ok(Listener::Tcp(listener))
+            }
+            #[cfg(unix)]
+            Transport::Unix(path) => {
+                let listener = tokio::net::UnixListener::bind(path).map_err(|err| {
+                    Error::ServerError(format!(
+                        "server failed to bind to {}: {err}",
+                        path.display()
+                    ))
+                })?;
+                Ok(Listener::Unix(listener, path.clone()))
+            }
+            Transport::InMemory => Ok(Listener::InMemory),
+        }
+    }
+
+    /// Returns the TCP addresses to try binding to, in order.
+    #[allow(deprecated)]
+    fn bind_addrs(&self) -> Vec<SocketAddr> {
+        if let Some(addr) = self.addr() {
+            return vec![*addr];
+        }
+        let config = &self.config;
+        let ports = if config.port == 0 && config.port_range_start < config.port_range_end {
+            (config.port_range_start..config.port_range_end)
+                .take(config.bind_max_retries + 1)
+                .collect()
+        } else {
+            vec![config.port]
+        };
+        ports
+            .into_iter()
+            .map(|port| SocketAddr::from((config.listen_addr, port)))
+            .collect()
+    }
 
+    /// Stops the server gracefully.
+    ///
+    /// New connections are refused and in-flight requests are drained,
+    /// up to the configured shutdown timeout.
+    pub async fn stop(&self) -> Result<(), Error> {
+        let Some(handle) = self.running.lock().unwrap().take() else {
+            return Err(Error::ServerError("not running".into()));
+        };
+        self.in_memory.close();
+        let _ = handle.shutdown_tx.send(());
+        handle
+            .task
+            .await
+            .map_err(|err| Error::ServerError(err.to_string()))?;
+        info!("{} server stopped", self.name());
         Ok(())
     }
 
+    /// Restarts the server on the same address.
+    pub async fn restart(&self) -> Result<(), Error> {
+        if self.is_running() {
+            self.stop().await?;
+        }
+        self.start().await
+    }
+
     pub fn name(&self) -> &str {
         self.name
     }
@@ -175,33 +335,119 @@ impl MockServer {
         }
     }
 
+    /// Returns the Unix domain socket path, if listening on one.
+    #[cfg(unix)]
+    pub fn unix_socket_path(&self) -> Option<&Path> {
+        match &self.transport {
+            Transport::Unix(path) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Returns a connector to the in-memory transport of the server.
+    ///
+    /// In-memory connections are served by running servers of any transport.
+    pub fn channel(&self) -> MockChannel {
+        MockChannel(self.in_memory.clone())
+    }
+
     pub fn is_running(&self) -> bool {
-        self.addr().is_some()
+        self.running.lock().unwrap().is_some()
     }
 
     pub fn mocks(&self) -> RwLockWriteGuard<'_, MockSet> {
-        self.state.mocks.write().unwrap()
+        self.state.mocks_mut()
+    }
+
+    /// Returns a cloneable handle to the server mocks.
+    pub fn handle(&self) -> MockServerHandle {
+        MockServerHandle::new(self.state.clone())
     }
 
     /// Builds and inserts a mock with default options.
-    pub fn mock<F>(&mut self, f: F)
+    ///
+    /// The mock is removed when the returned guard is dropped.
+    pub fn mock<F>(&self, f: F) -> MockGuard
     where
         F: FnOnce(When, Then),
     {
-        let mock = Mock::new(f);
-        self.state.mocks.write().unwrap().insert(mock);
+        self.handle().mock(f)
     }
 
     /// Builds and inserts a mock with options.
-    pub fn mock_with_options<F>(&mut self, priority: u8, limit: Option<usize>, f: F)
+    ///
+    /// The mock is removed when the returned guard is dropped.
+    pub fn mock_with_options<F>(&self, priority: u8, limit: Option<usize>, f: F) -> MockGuard
     where
         F: FnOnce(When, Then),
     {
-        let mut mock = Mock::new(f).with_priority(priority);
-        if let Some(limit) = limit {
-            mock = mock.with_limit(limit);
-        }
-        self.state.mocks.write().unwrap().insert(mock);
+        self.handle().mock_with_options(priority, limit, f)
+    }
+
+    /// Builds and inserts a mock in a group.
+    ///
+    /// The mock is removed when the returned guard is dropped.
+    pub fn mock_in_group<F>(&self, group: impl Into<String>, f: F) -> MockGuard
+    where
+        F: FnOnce(When, Then),
+    {
+        self.handle().mock_in_group(group, f)
+    }
+
+    /// Disables the mocks of a group, so they are not matched.
+    pub fn disable_group(&self, group: &str) {
+        self.handle().disable_group(group);
+    }
+
+    /// Enables the mocks of a group.
+    pub fn enable_group(&self, group: &str) {
+        self.handle().enable_group(group);
+    }
+
+    /// Resets the match counters of the mocks of a group.
+    pub fn reset_group(&self, group: &str) {
+        self.handle().reset_group(group);
+    }
+
+    /// Removes the mocks of a group, returning the number of mocks removed.
+    pub fn remove_group(&self, group: &str) -> usize {
+        self.handle().remove_group(group)
+    }
+
+    /// Enables the `grpc.health.v1.Health` service.
+    ///
+    /// The overall server status (empty service name) is initialized as serving.
+    pub fn with_health(self) -> Self {
+        self.state.health.get_or_init(HealthState::new);
+        self
+    }
+
+    /// Sets the health serving status of a service.
+    ///
+    /// Enables the `grpc.health.v1.Health` service if it is not already enabled.
+    pub fn set_health(&self, service: impl Into<String>, status: ServingStatus) {
+        self.state
+            .health
+            .get_or_init(HealthState::new)
+            .set(service, status);
+    }
+
+    /// Returns the health serving status of a service.
+    pub fn health(&self, service: &str) -> Option<ServingStatus> {
+        self.state
+            .health
+            .get()
+            .and_then(|health| health.get(service))
+    }
+
+    /// Enables CORS handling with a policy (HTTP only).
+    ///
+    /// Preflight requests are answered automatically and CORS headers
+    /// are added to responses for allowed origins.
+    /// Replaces any policy set before.
+    pub fn with_cors(self, cors: Cors) -> Self {
+        *self.state.cors.write().unwrap() = Some(cors);
+        self
     }
 }
 
@@ -209,18 +455,115 @@ impl MockServer {
 #[derive(Debug, Default)]
 pub struct MockServerState {
     pub mocks: RwLock<MockSet>,
+    pub health: OnceLock<HealthState>,
+    pub cors: RwLock<Option<Cors>>,
+    /// IDs of mocks removed while the mocks were locked, see [`MockServerState::remove_mock`].
+    removed: Mutex<HashSet<Uuid>>,
 }
 
 impl MockServerState {
     pub fn new(mocks: MockSet) -> Self {
         Self {
             mocks: RwLock::new(mocks),
+            health: OnceLock::new(),
+            cors: RwLock::default(),
+            removed: Mutex::default(),
         }
     }
 
     pub fn mocks(&self) -> RwLockReadGuard<'_, MockSet> {
+        self.apply_removals();
         self.mocks.read().unwrap()
     }
+
+    /// Returns a write guard to the mocks, applying deferred removals.
+    pub fn mocks_mut(&self) -> RwLockWriteGuard<'_, MockSet> {
+        let mut mocks = self.mocks.write().unwrap();
+        for id in self.removed.lock().unwrap().drain() {
+            mocks.remove_by_id(&id);
+        }
+        mocks
+    }
+
+    /// Removes a mock by ID without blocking.
+    ///
+    /// If the mocks are locked, e.g. by a guard held on the same thread, the mock
+    /// is no longer matched and is removed the next time the mocks are accessed.
+    pub fn remove_mock(&self, id: &Uuid) {
+        match self.mocks.try_write() {
+            Ok(mut mocks) => {
+                mocks.remove_by_id(id);
+            }
+            Err(TryLockError::WouldBlock) => {
+                self.removed.lock().unwrap().insert(*id);
+            }
+            Err(TryLockError::Poisoned(_)) => {}
+        }
+    }
+
+    /// Applies deferred removals, if the mocks are not locked.
+    fn apply_removals(&self) {
+        let mut removed = self.removed.lock().unwrap();
+        if removed.is_empty() {
+            return;
+        }
+        if let Ok(mut mocks) = self.mocks.try_write() {
+            for id in removed.drain() {
+                mocks.remove_by_id(&id);
+            }
+        }
+    }
+
+    /// Returns the candidate mocks for a request, excluding removed mocks.
+    fn candidates(&self, request: &Request) -> Vec<Arc<Mock>> {
+        let mut candidates = self.mocks().candidates(request);
+        let removed = self.removed.lock().unwrap();
+        if !removed.is_empty() {
+            candidates.retain(|mock| !removed.contains(mock.id()));
+        }
+        candidates
+    }
+
+    /// Matches a request to a mock, evaluating async matchers outside of the mockset lock.
+    pub async fn match_by_request(&self, request: &Request) -> Option<Arc<Mock>> {
+        let candidates = self.candidates(request);
+        match_candidates(candidates, request).await
+    }
+
+    /// Matches a message of a bidirectional stream to a bidi mock.
+    pub async fn match_stream_message(&self, request: &Request) -> Option<Arc<Mock>> {
+        let mut candidates = self.candidates(request);
+        candidates.retain(|mock| mock.is_bidi());
+        match_candidates(candidates, request).await
+    }
+}
+
+/// Returns the first candidate mock matching a request.
+async fn match_candidates(candidates: Vec<Arc<Mock>>, request: &Request) -> Option<Arc<Mock>> {
+    for mock in candidates {
+        match mock.matches_async(request).await {
+            Ok(()) => return Some(mock),
+            Err(reason) => debug!(id = %mock.id(), reason, "mock not matched"),
+        }
+    }
+    None
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        // Signal shutdown, connections are drained in the background
+        self.in_memory.close();
+        if let Ok(Some(handle)) = self.running.get_mut().map(Option::take) {
+            let _ = handle.shutdown_tx.send(());
+        }
+    }
+}
+
+/// A running server task.
+#[derive(Debug)]
+struct RunningServer {
+    shutdown_tx: oneshot::Sender<()>,
+    task: JoinHandle<()>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -238,13 +581,14 @@ impl std::fmt::Display for ServerKind {
     }
 }
 
-/// Runs the main server loop to accept and serve connections.
+/// Runs the main server loop to accept and serve connections until shutdown.
 async fn run_server<S, B>(
-    listener: TcpListener,
-    server_kind: ServerKind,
+    listener: Listener,
+    mut in_memory_rx: mpsc::UnboundedReceiver<DuplexStream>,
     service: S,
-) -> Result<(), Error>
-where
+    mut shutdown_rx: oneshot::Receiver<()>,
+    shutdown_timeout: Duration,
+) where
     S: Service<http::Request<Incoming>, Response = http::Response<B>> + Clone + Send + 'static,
     S::Future: Send + 'static,
     S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
@@ -252,61 +596,96 @@ where
     B::Data: Send + 'static,
     B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
 {
-    // Spawn task to accept new connections
-    tokio::spawn(async move {
-        loop {
-            let (stream, addr) = match listener.accept().await {
+    let graceful = GracefulShutdown::new();
+    let mut connections = JoinSet::new();
+    loop {
+        let (stream, addr) = tokio::select! {
+            conn = listener.accept() => match conn {
                 Ok(conn) => conn,
                 Err(err) => {
                     error!("connection accept error: {err}");
                     continue;
                 }
-            };
-            debug!("connection accepted: {addr}");
-            let io = TokioIo::new(stream);
-            let service = service.clone();
-            // Spawn task to serve connection
-            tokio::spawn(async move {
-                let builder = match server_kind {
-                    ServerKind::Http => conn::auto::Builder::new(TokioExecutor::new()),
-                    ServerKind::Grpc => conn::auto::Builder::new(TokioExecutor::new()).http2_only(),
-                };
-                if let Err(err) = builder.serve_connection(io, service).await {
-                    debug!("connection error: {err}");
-                }
-                debug!("connection dropped: {addr}");
-            });
-        }
-    });
+            },
+            Some(stream) = in_memory_rx.recv() => (Box::new(stream) as Box<dyn Io>, "memory".into()),
+            // Reap finished connections
+            Some(_) = connections.join_next(), if !connections.is_empty() => continue,
+            _ = &mut shutdown_rx => break,
+        };
+        debug!("connection accepted: {addr}");
+        let io = TokioIo::new(stream);
+        let service = service.clone();
+        let watcher = graceful.watcher();
+        // Spawn task to serve connection
+        connections.spawn(async move {
+            // Serves HTTP/1.1 and HTTP/2, as gRPC-Web clients use HTTP/1.1
+            let mut builder = conn::auto::Builder::new(TokioExecutor::new());
+            // Enables WebSocket over HTTP/2 (RFC 8441)
+            builder.http2().enable_connect_protocol();
+            let conn = builder.serve_connection_with_upgrades(io, service);
+            if let Err(err) = watcher.watch(conn).await {
+                debug!("connection error: {err}");
+            }
+            debug!("connection dropped: {addr}");
+        });
+    }
 
-    Ok(())
+    // Stop accepting connections and drain in-flight requests
+    drop(listener);
+    debug!("shutting down, draining {} connections", connections.len());
+    if tokio::time::timeout(shutdown_timeout, graceful.shutdown())
+        .await
+        .is_err()
+    {
+        debug!("shutdown timeout elapsed, aborting connections");
+    }
+    connections.shutdown().await;
 }
 
 #[derive(Debug)]
 pub struct MockServerConfig {
     pub listen_addr: IpAddr,
+    /// Port to bind to, or 0 for an OS-assigned port.
+    pub port: u16,
+    /// Start of a port range to bind to, if `port` is 0.
+    #[deprecated(since = "0.4.0", note = "please use `port` instead")]
     pub port_range_start: u16,
+    /// End of a port range to bind to, exclusive.
+    #[deprecated(since = "0.4.0", note = "please use `port` instead")]
     pub port_range_end: u16,
+    /// Maximum number of ports of the range to try after the first.
+    #[deprecated(since = "0.4.0", note = "please use `port` instead")]
     pub bind_max_retries: usize,
     pub ready_connect_max_retries: usize,
     pub ready_connect_timeout: Duration,
+    /// Maximum time to drain in-flight requests on shutdown.
+    pub shutdown_timeout: Duration,
 }
 
 impl MockServerConfig {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Sets the port to bind to.
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
 }
 
 impl Default for MockServerConfig {
+    #[allow(deprecated)]
     fn default() -> Self {
         Self {
             listen_addr: IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)),
-            port_range_start: 10000,
-            port_range_end: 30000,
+            port: 0,
+            port_range_start: 0,
+            port_range_end: 0,
             bind_max_retries: 10,
             ready_connect_max_retries: 30,
             ready_connect_timeout: Duration::from_millis(10),
+            shutdown_timeout: Duration::from_secs(5),
         }
     }
 }
@@ -320,4 +699,11 @@ mod tests {
         fn is_send<T: Send>() {}
         is_send::<MockServer>();
     }
+
+    #[test]
+    fn test_mock_server_sync() {
+        fn is_sync<T: Sync>() {}
+        is_sync::<MockServer>();
+        is_sync::<MockServerHandle>();
+    }
 }
@@ -0,0 +1,66 @@
+//! Mock guard
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use super::MockServerState;
+
+/// A guard for a mock registered to a [`MockServer`](super::MockServer).
+///
+/// The mock is removed from the server when the guard is dropped,
+/// unless it is detached with [`MockGuard::detach`]. Dropping a guard never
+/// blocks: if the mocks are locked, the removal is deferred.
+#[must_use = "the mock is removed when the guard is dropped"]
+#[derive(Debug)]
+pub struct MockGuard {
+    id: Uuid,
+    state: Option<Arc<MockServerState>>,
+}
+
+impl MockGuard {
+    pub(super) fn new(id: Uuid, state: Arc<MockServerState>) -> Self {
+        Self {
+            id,
+            state: Some(state),
+        }
+    }
+
+    /// Returns the mock ID.
+    pub fn id(&self) -> &Uuid {
+        &self.id
+    }
+
+    /// Returns the number of times the mock has been matched.
+    pub fn hits(&self) -> usize {
+        self.state
+            .as_ref()
+            .and_then(|state| {
+                state
+                    .mocks()
+                    .find(|mock| mock.id() == &self.id)
+                    .map(|mock| mock.match_count())
+            })
+            .unwrap_or_default()
+    }
+
+    /// Asserts that the mock has been matched `n` times.
+    #[track_caller]
+    pub fn assert_hits(&self, n: usize) {
+        let hits = self.hits();
+        assert_eq!(hits, n, "mock {} expected {n} hits, got {hits}", self.id);
+    }
+
+    /// Detaches the guard, keeping the mock registered, and returns its ID.
+    pub fn detach(mut self) -> Uuid {
+        self.state = None;
+        self.id
+    }
+}
+
+impl Drop for MockGuard {
+    fn drop(&mut self) {
+        if let Some(state) = self.state.take() {
+            state.remove_mock(&self.id);
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! Mock server handle
+use std::sync::{Arc, RwLockReadGuard};
+
+use uuid::Uuid;
+
+use super::{MockGuard, MockServerState};
+use crate::{
+    mock::Mock,
+    mock_builder::{Then, When},
+    mock_set::MockSet,
+};
+
+/// A cloneable handle to the mocks of a [`MockServer`](super::MockServer).
+///
+/// Methods take `&self` and are safe to use from any task, e.g. to share
+/// one server across a test suite.
+#[derive(Debug, Clone)]
+pub struct MockServerHandle {
+    state: Arc<MockServerState>,
+}
+
+impl MockServerHandle {
+    pub(super) fn new(state: Arc<MockServerState>) -> Self {
+        Self { state }
+    }
+
+    /// Builds and inserts a mock with default options.
+    ///
+    /// The mock is removed when the returned guard is dropped.
+    pub fn mock<F>(&self, f: F) -> MockGuard
+    where
+        F: FnOnce(When, Then),
+    {
+        self.insert(Mock::new(f))
+    }
+
+    /// Builds and inserts a mock with options.
+    ///
+    /// The mock is removed when the returned guard is dropped.
+    pub fn mock_with_options<F>(&self, priority: u8, limit: Option<usize>, f: F) -> MockGuard
+    where
+        F: FnOnce(When, Then),
+    {
+        let mut mock = Mock::new(f).with_priority(priority);
+        if let Some(limit) = limit {
+            mock = mock.with_limit(limit);
+        }
+        self.insert(mock)
+    }
+
+    /// Builds and inserts a mock in a group.
+    ///
+    /// The mock is removed when the returned guard is dropped.
+    pub fn mock_in_group<F>(&self, group: impl Into<String>, f: F) -> MockGuard
+    where
+        F: FnOnce(When, Then),
+    {
+        self.insert(Mock::new(f).with_group(group))
+    }
+
+    /// Inserts a mock.
+    ///
+    /// The mock is removed when the returned guard is dropped.
+    pub fn insert(&self, mock: Mock) -> MockGuard {
+        let id = mock.id;
+        self.state.mocks_mut().insert(mock);
+        MockGuard::new(id, self.state.clone())
+    }
+
+    /// Removes a mock by ID.
+    pub fn remove(&self, id: &Uuid) -> Option<Arc<Mock>> {
+        self.state.mocks_mut().remove_by_id(id)
+    }
+
+    /// Returns a mock by ID.
+    pub fn get(&self, id: &Uuid) -> Option<Arc<Mock>> {
+        self.mocks().find(|mock| mock.id() == id).cloned()
+    }
+
+    /// Returns `true` if the server has a mock with the ID.
+    pub fn contains(&self, id: &Uuid) -> bool {
+        self.mocks().iter().any(|mock| mock.id() == id)
+    }
+
+    /// Returns the number of mocks.
+    pub fn len(&self) -> usize {
+        self.mocks().len()
+    }
+
+    /// Returns true if the server has no mocks.
+    pub fn is_empty(&self) -> bool {
+        self.mocks().is_empty()
+    }
+
+    /// Removes all mocks.
+    pub fn clear(&self) {
+        self.state.mocks_mut().clear();
+    }
+
+    /// Disables the mocks of a group, so they are not matched.
+    pub fn disable_group(&self, group: &str) {
+        self.state.mocks_mut().disable_group(group);
+    }
+
+    /// Enables the mocks of a group.
+    pub fn enable_group(&self, group: &str) {
+        self.state.mocks_mut().enable_group(group);
+    }
+
+    /// Returns `true` if the mocks of a group are enabled.
+    pub fn is_group_enabled(&self, group: &str) -> bool {
+        self.mocks().is_group_enabled(group)
+    }
+
+    /// Resets the match counters of the mocks of a group.
+    pub fn reset_group(&self, group: &str) {
+        self.mocks().reset_group(group);
+    }
+
+    /// Removes the mocks of a group, returning the number of mocks removed.
+    pub fn remove_group(&self, group: &str) -> usize {
+        self.state.mocks_mut().remove_group(group)
+    }
+
+    /// Returns a read guard to the mocks.
+    pub fn mocks(&self) -> RwLockReadGuard<'_, MockSet> {
+        self.state.mocks()
+    }
+}
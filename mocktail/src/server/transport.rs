@@ -0,0 +1,210 @@
+//! Server transports
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::{
+    fmt, io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use hyper_util::rt::TokioIo;
+#[cfg(unix)]
+use tokio::net::{UnixListener, UnixStream};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, DuplexStream},
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+};
+
+/// Buffer size of in-memory connections.
+const IN_MEMORY_BUFFER_SIZE: usize = 64 * 1024;
+
+/// A connection stream.
+pub(super) trait Io: AsyncRead + AsyncWrite + Unpin + Send + 'static {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send + 'static> Io for T {}
+
+/// The transport a server listens on.
+#[derive(Debug, Clone, Default)]
+pub(super) enum Transport {
+    #[default]
+    Tcp,
+    #[cfg(unix)]
+    Unix(PathBuf),
+    /// In-memory connections only, see [`MockChannel`].
+    InMemory,
+}
+
+/// A bound listener.
+#[derive(Debug)]
+pub(super) enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener, PathBuf),
+    InMemory,
+}
+
+impl Listener {
+    /// Accepts a new connection.
+    ///
+    /// Never completes for in-memory listeners, as connections are
+    /// received from [`InMemory`] instead.
+    pub async fn accept(&self) -> io::Result<(Box<dyn Io>, String)> {
+        match self {
+            Listener::Tcp(listener) => {
+                let (stream, addr) = listener.accept().await?;
+                Ok((Box::new(stream), addr.to_string()))
+            }
+            #[cfg(unix)]
+            Listener::Unix(listener, path) => {
+                let (stream, _) = listener.accept().await?;
+                Ok((Box::new(stream), path.display().to_string()))
+            }
+            Listener::InMemory => std::future::pending().await,
+        }
+    }
+
+    /// Returns the local socket address of a TCP listener.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        match self {
+            Listener::Tcp(listener) => listener.local_addr().ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the endpoint clients connect to.
+    pub fn endpoint(&self) -> Endpoint {
+        match self {
+            Listener::Tcp(listener) => match listener.local_addr() {
+                Ok(addr) => Endpoint::Tcp(addr),
+                Err(_) => Endpoint::Unknown,
+            },
+            #[cfg(unix)]
+            Listener::Unix(_, path) => Endpoint::Unix(path.clone()),
+            Listener::InMemory => Endpoint::InMemory,
+        }
+    }
+}
+
+/// The endpoint of a bound listener, used to probe a running server.
+#[derive(Debug, Clone)]
+pub(super) enum Endpoint {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(PathBuf),
+    InMemory,
+    /// The listener has no local address.
+    Unknown,
+}
+
+impl Endpoint {
+    /// Returns true if the endpoint accepts connections.
+    pub async fn is_ready(&self, timeout: Duration) -> bool {
+        match self {
+            Endpoint::Tcp(addr) => {
+                let connect_addr = match addr.ip() {
+                    IpAddr::V4(ip) if ip.is_unspecified() => {
+                        (Ipv4Addr::LOCALHOST, addr.port()).into()
+                    }
+                    IpAddr::V6(ip) if ip.is_unspecified() => {
+                        (Ipv6Addr::LOCALHOST, addr.port()).into()
+                    }
+                    _ => *addr,
+                };
+                matches!(
+                    tokio::time::timeout(timeout, TcpStream::connect(connect_addr)).await,
+                    Ok(Ok(_))
+                )
+            }
+            #[cfg(unix)]
+            Endpoint::Unix(path) => matches!(
+                tokio::time::timeout(timeout, UnixStream::connect(path)).await,
+                Ok(Ok(_))
+            ),
+            Endpoint::InMemory => true,
+            Endpoint::Unknown => false,
+        }
+    }
+}
+
+impl fmt::Display for Listener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Listener::Tcp(listener) => match listener.local_addr() {
+                Ok(addr) => write!(f, "{addr}"),
+                Err(_) => write!(f, "tcp"),
+            },
+            #[cfg(unix)]
+            Listener::Unix(_, path) => write!(f, "{}", path.display()),
+            Listener::InMemory => write!(f, "memory"),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Listener {
+    fn drop(&mut self) {
+        if let Listener::Unix(_, path) = self {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// In-memory connections to a server.
+///
+/// Shared between a server and its channels, so channels reconnect after a restart.
+#[derive(Debug, Clone, Default)]
+pub(super) struct InMemory(Arc<Mutex<Option<mpsc::UnboundedSender<DuplexStream>>>>);
+
+impl InMemory {
+    /// Starts accepting connections, returning the server side receiver.
+    pub fn listen(&self) -> mpsc::UnboundedReceiver<DuplexStream> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        *self.0.lock().unwrap() = Some(tx);
+        rx
+    }
+
+    /// Stops accepting connections.
+    pub fn close(&self) {
+        self.0.lock().unwrap().take();
+    }
+
+    /// Opens a connection, returning the client side stream.
+    pub fn connect(&self) -> io::Result<DuplexStream> {
+        let not_connected = || io::Error::new(io::ErrorKind::NotConnected, "server not running");
+        let tx = self.0.lock().unwrap().clone().ok_or_else(not_connected)?;
+        let (client, server) = tokio::io::duplex(IN_MEMORY_BUFFER_SIZE);
+        tx.send(server).map_err(|_| not_connected())?;
+        Ok(client)
+    }
+}
+
+/// A connector to the in-memory transport of a [`MockServer`](super::MockServer).
+///
+/// Implements `tower::Service<Uri>`, so it can be used as a hyper or tonic
+/// connector, e.g. with `Endpoint::connect_with_connector`. The URI is ignored.
+#[derive(Debug, Clone)]
+pub struct MockChannel(pub(super) InMemory);
+
+impl MockChannel {
+    /// Opens a connection.
+    pub fn connect(&self) -> io::Result<TokioIo<DuplexStream>> {
+        self.0.connect().map(TokioIo::new)
+    }
+}
+
+impl tower_service::Service<http::Uri> for MockChannel {
+    type Response = TokioIo<DuplexStream>;
+    type Error = io::Error;
+    type Future = std::future::Ready<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _uri: http::Uri) -> Self::Future {
+        std::future::ready(self.connect())
+    }
+}
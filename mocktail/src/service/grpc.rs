@@ -2,19 +2,41 @@
 use std::{convert::Infallible, sync::Arc};
 
 use bytes::{Bytes, BytesMut};
-use futures::{future::BoxFuture, StreamExt};
+use futures::future::BoxFuture;
 use http::{HeaderMap, HeaderValue};
 use http_body::Frame;
 use http_body_util::{BodyExt, StreamBody};
 use hyper::{body::Incoming, service::Service};
-use tokio::sync::mpsc;
+use prost::Message;
+use tokio::{sync::mpsc, time::Instant};
 use tokio_stream::wrappers::ReceiverStream;
 use tracing::debug;
 
-use crate::{request::Request, server::MockServerState, service::http::empty, Code};
+use crate::{
+    body::Body,
+    ext::MessageExt,
+    grpc::{
+        codec::{self, GrpcFrameDecoder},
+        error_details::Status,
+        health::{self, HealthCheckRequest, HealthCheckResponse, ServingStatus},
+        timeout::parse_timeout,
+        CompressionEncoding,
+    },
+    response::Response,
+    server::MockServerState,
+    service::http::empty,
+    Code,
+};
 
 use super::http::BoxBody;
 
+mod connect;
+use connect::Connect;
+mod protocol;
+use protocol::{Protocol, TextDecoder};
+mod stream;
+use stream::{compress, Framing};
+
 /// Mock gRPC service.
 #[derive(Debug, Clone)]
 pub struct GrpcMockService {
@@ -44,87 +66,319 @@ impl Service<http::Request<Incoming>> for GrpcMockService {
                     .body(empty())
                     .unwrap());
             }
-            let content_type = req.headers().get("content-type");
-            if !content_type.is_some_and(|v| {
-                v.to_str()
-                    .unwrap_or_default()
-                    .starts_with("application/grpc")
-            }) {
+            if let Some(connect) = req
+                .headers()
+                .get("content-type")
+                .and_then(|v| Connect::from_content_type(v.to_str().unwrap_or_default()))
+            {
+                return Ok(connect.response(state, req).await);
+            }
+            let protocol = req
+                .headers()
+                .get("content-type")
+                .and_then(|v| Protocol::from_content_type(v.to_str().unwrap_or_default()));
+            let Some(protocol) = protocol else {
                 return Ok(http::Response::builder()
                     .status(http::StatusCode::UNSUPPORTED_MEDIA_TYPE)
-                    .header("Accept-Post", "application/grpc")
+                    .header(
+                        "Accept-Post",
+                        "application/grpc, application/grpc-web, application/grpc-web-text, \
+                         application/proto, application/json, application/connect+proto, \
+                         application/connect+json",
+                    )
                     .body(empty())
                     .unwrap());
+            };
+
+            if state.health.get().is_some() && req.uri().path().starts_with(health::SERVICE_PATH) {
+                return Ok(health_response(state, req, protocol));
             }
 
+            // Get request and accepted response message compression encodings
+            let encoding = match req.headers().get("grpc-encoding") {
+                Some(value) => {
+                    match CompressionEncoding::from_header_value(value.to_str().unwrap_or_default())
+                    {
+                        Ok(encoding) => encoding,
+                        Err(err) => {
+                            let mut response =
+                                status_response(protocol, Code::Unimplemented, &err.to_string());
+                            response.headers_mut().insert(
+                                "grpc-accept-encoding",
+                                HeaderValue::from_str(&CompressionEncoding::accept_header_value())
+                                    .unwrap(),
+                            );
+                            return Ok(response);
+                        }
+                    }
+                }
+                None => None,
+            };
+            let accept_encodings = req
+                .headers()
+                .get("grpc-accept-encoding")
+                .map(|value| {
+                    CompressionEncoding::from_accept_header_value(
+                        value.to_str().unwrap_or_default(),
+                    )
+                })
+                .unwrap_or_default();
+
+            // Get request deadline
+            let deadline = req
+                .headers()
+                .get("grpc-timeout")
+                .and_then(|value| parse_timeout(value.to_str().unwrap_or_default()))
+                .map(|timeout| Instant::now() + timeout);
+
             let (parts, body) = req.into_parts();
-            let mut stream = body.into_data_stream();
-
-            // Create response stream
-            let (response_tx, response_rx) =
-                mpsc::channel::<Result<Frame<Bytes>, hyper::Error>>(32);
-            let response_stream = ReceiverStream::new(response_rx);
-            let response_body = BoxBody::new(StreamBody::new(response_stream));
-            let response = http::Response::builder()
-                .header("content-type", "application/grpc")
+            let framing = GrpcFraming {
+                protocol,
+                text_decoder: TextDecoder::default(),
+                response_decoder: GrpcFrameDecoder::new(),
+            };
+            let (headers, response_body) = stream::serve(
+                framing,
+                state,
+                parts,
+                body,
+                encoding,
+                accept_encodings,
+                deadline,
+            )
+            .await;
+
+            let mut response = http::Response::builder()
+                .header("content-type", protocol.content_type())
+                .header(
+                    "grpc-accept-encoding",
+                    CompressionEncoding::accept_header_value(),
+                )
                 .body(response_body)
                 .unwrap();
+            response.headers_mut().extend(headers);
 
-            // Spawn task to handle request
-            tokio::spawn(async move {
-                let mut request = Request::from_parts(parts);
-                let mut matched = false;
-                let mut buf = BytesMut::new();
-
-                while let Some(Ok(chunk)) = stream.next().await {
-                    debug!(?chunk, "received chunk");
-                    // Add chunk to body buffer
-                    buf.extend(chunk);
-
-                    // Match request to mock
-                    request = request.with_body(buf.clone().freeze());
-                    let mock = state.mocks().match_by_request(&request);
-                    if let Some(mock) = mock {
-                        matched = true;
-                        debug!("mock found, sending response");
-                        let mut response = mock.response;
-                        // Send data frames
-                        if !response.body().is_empty() {
-                            while let Some(chunk) = response.body.next().await {
-                                let _ = response_tx.send(Ok(Frame::data(chunk))).await;
-                            }
+            Ok(response)
+        };
+        Box::pin(fut)
+    }
+}
+
+/// Serves the `grpc.health.v1.Health` service.
+fn health_response(
+    state: Arc<MockServerState>,
+    req: http::Request<Incoming>,
+    protocol: Protocol,
+) -> http::Response<BoxBody> {
+    let (parts, body) = req.into_parts();
+
+    // Create response stream
+    let (response_tx, response_rx) = mpsc::channel::<Result<Frame<Bytes>, hyper::Error>>(32);
+    let response_stream = ReceiverStream::new(response_rx);
+    let response_body = BoxBody::new(StreamBody::new(response_stream));
+    let response = http::Response::builder()
+        .header("content-type", protocol.content_type())
+        .body(response_body)
+        .unwrap();
+
+    // Spawn task to handle request
+    tokio::spawn(async move {
+        let Some(health) = state.health.get() else {
+            return;
+        };
+        let Ok(mut body) = body.collect().await.map(|body| body.to_bytes()) else {
+            return;
+        };
+        if protocol == Protocol::GrpcWebText {
+            body = TextDecoder::default().decode(&body).unwrap_or_default();
+        }
+        let request = HealthCheckRequest::decode(body.get(5..).unwrap_or_default());
+        let Ok(request) = request else {
+            let _ = response_tx
+                .send(Ok(protocol.trailers_frame(status_trailer(
+                    Code::InvalidArgument,
+                    "invalid health check request",
+                ))))
+                .await;
+            return;
+        };
+        debug!(
+            service = request.service,
+            path = parts.uri.path(),
+            "health request"
+        );
+        match parts.uri.path() {
+            health::CHECK_PATH => {
+                let trailers = if let Some(status) = health.get(&request.service) {
+                    let message = HealthCheckResponse::new(status).to_bytes();
+                    let _ = response_tx.send(Ok(protocol.data_frame(message))).await;
+                    status_trailer(Code::Ok, "")
+                } else {
+                    status_trailer(Code::NotFound, "unknown service")
+                };
+                let _ = response_tx
+                    .send(Ok(protocol.trailers_frame(trailers)))
+                    .await;
+            }
+            health::WATCH_PATH => {
+                let mut statuses = health.subscribe();
+                let mut last_status = None;
+                loop {
+                    let status = statuses
+                        .borrow_and_update()
+                        .get(&request.service)
+                        .copied()
+                        .unwrap_or(ServingStatus::ServiceUnknown);
+                    if last_status != Some(status) {
+                        debug!(service = request.service, ?status, "sending health status");
+                        let message = HealthCheckResponse::new(status).to_bytes();
+                        if response_tx
+                            .send(Ok(protocol.data_frame(message)))
+                            .await
+                            .is_err()
+                        {
+                            break;
                         }
-                        // Send trailers frame
-                        let mut trailers = HeaderMap::from(response.headers().clone());
-                        trailers
-                            .insert("grpc-status", response.status().as_grpc().to_header_value());
-                        if let Some(message) = response.message() {
-                            trailers
-                                .insert("grpc-message", HeaderValue::from_str(message).unwrap());
+                        last_status = Some(status);
+                    }
+                    tokio::select! {
+                        changed = statuses.changed() => {
+                            if changed.is_err() {
+                                break;
+                            }
                         }
-                        let _ = response_tx.send(Ok(Frame::trailers(trailers))).await;
-                        // Clear body buffer
-                        buf.clear();
+                        _ = response_tx.closed() => break,
                     }
                 }
-                debug!("request stream closed");
-                if !matched {
-                    debug!(?request, "no mocks found, sending error");
-                    let _ = response_tx
-                        .send(Ok(Frame::trailers(mock_not_found_trailer())))
-                        .await;
-                }
-            });
+                debug!(service = request.service, "health watch closed");
+            }
+            _ => {
+                let _ = response_tx
+                    .send(Ok(protocol.trailers_frame(status_trailer(
+                        Code::Unimplemented,
+                        "method not found",
+                    ))))
+                    .await;
+            }
+        }
+    });
 
-            Ok(response)
+    response
+}
+
+/// gRPC framing of a streaming call.
+struct GrpcFraming {
+    protocol: Protocol,
+    text_decoder: TextDecoder,
+    /// Decodes response messages to compress, which may be split across chunks.
+    response_decoder: GrpcFrameDecoder,
+}
+
+impl Framing for GrpcFraming {
+    fn encoding_header(&self) -> &'static str {
+        "grpc-encoding"
+    }
+
+    fn decode_chunk(&mut self, chunk: Bytes) -> Result<Bytes, String> {
+        if self.protocol != Protocol::GrpcWebText {
+            return Ok(chunk);
+        }
+        self.text_decoder
+            .decode(&chunk)
+            .map_err(|_| "invalid base64 request body".to_string())
+    }
+
+    fn request_body(&self, messages: &[Bytes]) -> Body {
+        let mut buf = BytesMut::new();
+        for message in messages {
+            buf.extend(codec::encode_frame(false, message));
+        }
+        Body::bytes(buf.freeze())
+    }
+
+    fn data_frame(
+        &mut self,
+        chunk: Bytes,
+        compression: Option<CompressionEncoding>,
+    ) -> Result<Frame<Bytes>, String> {
+        let chunk = match compression {
+            Some(encoding) => compress_frames(encoding, &mut self.response_decoder, chunk)?,
+            None => chunk,
         };
-        Box::pin(fut)
+        Ok(self.protocol.data_frame(chunk))
+    }
+
+    fn end_data_frame(&mut self) -> Option<Frame<Bytes>> {
+        // Pass through any remaining bytes that are not a complete message
+        let decoder = std::mem::take(&mut self.response_decoder);
+        decoder.has_remaining().then(|| {
+            self.protocol
+                .data_frame(Bytes::copy_from_slice(decoder.remaining()))
+        })
+    }
+
+    fn response_end_frame(&self, response: &Response) -> Frame<Bytes> {
+        self.protocol.trailers_frame(response_trailers(response))
+    }
+
+    fn error_end_frame(&self, code: Code, message: &str) -> Frame<Bytes> {
+        self.protocol.trailers_frame(status_trailer(code, message))
+    }
+}
+
+/// Compresses the length-prefixed messages completed by a chunk.
+///
+/// Incomplete messages are buffered by the decoder until the next chunk.
+fn compress_frames(
+    encoding: CompressionEncoding,
+    decoder: &mut GrpcFrameDecoder,
+    chunk: Bytes,
+) -> Result<Bytes, String> {
+    let mut buf = BytesMut::new();
+    for frame in decoder.decode(&chunk) {
+        let data = if frame.compressed {
+            frame.data
+        } else {
+            compress(encoding, &frame.data)?
+        };
+        buf.extend(codec::encode_frame(true, &data));
     }
+    Ok(buf.freeze())
 }
 
-fn mock_not_found_trailer() -> HeaderMap {
+/// Returns the trailers of a mock response, including its status.
+fn response_trailers(response: &Response) -> HeaderMap {
+    let mut trailers = HeaderMap::from(response.trailers().clone());
+    trailers.insert("grpc-status", response.code().to_header_value());
+    if let Some(message) = response.message() {
+        trailers.insert("grpc-message", HeaderValue::from_str(message).unwrap());
+    }
+    if !response.error_details().is_empty() {
+        let status = Status::new(
+            response.code(),
+            response.message().unwrap_or_default(),
+            response.error_details(),
+        );
+        trailers.insert("grpc-status-details-bin", status.to_header_value());
+    }
+    trailers
+}
+
+/// Returns a trailers-only response with a status.
+fn status_response(protocol: Protocol, code: Code, message: &str) -> http::Response<BoxBody> {
+    let mut response = http::Response::builder()
+        .header("content-type", protocol.content_type())
+        .body(empty())
+        .unwrap();
+    response.headers_mut().extend(status_trailer(code, message));
+    response
+}
+
+fn status_trailer(code: Code, message: &str) -> HeaderMap {
     let mut headers = HeaderMap::new();
-    headers.insert("grpc-status", Code::NotFound.to_header_value());
-    headers.insert("grpc-message", HeaderValue::from_static("mock not found"));
+    headers.insert("grpc-status", code.to_header_value());
+    if !message.is_empty() {
+        headers.insert("grpc-message", HeaderValue::from_str(message).unwrap());
+    }
     headers
 }
@@ -0,0 +1,464 @@
+//! Connect protocol
+//!
+//! Implements the [Connect protocol](https://connectrpc.com/docs/protocol),
+//! serving the same mocks to Connect clients as to gRPC clients.
+use std::{sync::Arc, time::Duration};
+
+use base64::{engine::general_purpose::STANDARD_NO_PAD, Engine};
+use bytes::{BufMut, Bytes, BytesMut};
+use http::{HeaderMap, HeaderName, HeaderValue};
+use http_body::Frame;
+use http_body_util::BodyExt;
+use hyper::body::Incoming;
+use serde_json::{json, Value};
+use tokio::time::Instant;
+use tracing::debug;
+
+use super::stream::{self, compress, with_deadline, Framing};
+use crate::{
+    body::Body,
+    grpc::{
+        codec::{self, GrpcFrameDecoder, HEADER_SIZE},
+        CompressionEncoding, ErrorDetail,
+    },
+    request::Request,
+    response::Response,
+    server::MockServerState,
+    service::http::{full, BoxBody},
+    Code,
+};
+
+/// End-of-stream envelope flag.
+const END_STREAM_FLAG: u8 = 0x02;
+
+/// A Connect message codec.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    Proto,
+    Json,
+}
+
+/// A Connect RPC type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connect {
+    Unary(Codec),
+    Streaming(Codec),
+}
+
+impl Connect {
+    /// Returns the Connect RPC type of a request content-type, if supported.
+    pub fn from_content_type(value: &str) -> Option<Self> {
+        match value.split(';').next().unwrap_or_default().trim() {
+            "application/proto" => Some(Self::Unary(Codec::Proto)),
+            "application/json" => Some(Self::Unary(Codec::Json)),
+            "application/connect+proto" => Some(Self::Streaming(Codec::Proto)),
+            "application/connect+json" => Some(Self::Streaming(Codec::Json)),
+            _ => None,
+        }
+    }
+
+    /// Returns the response content-type.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Connect::Unary(Codec::Proto) => "application/proto",
+            Connect::Unary(Codec::Json) => "application/json",
+            Connect::Streaming(Codec::Proto) => "application/connect+proto",
+            Connect::Streaming(Codec::Json) => "application/connect+json",
+        }
+    }
+
+    /// Serves a Connect request.
+    pub async fn response(
+        self,
+        state: Arc<MockServerState>,
+        req: http::Request<Incoming>,
+    ) -> http::Response<BoxBody> {
+        if req
+            .headers()
+            .get("connect-protocol-version")
+            .is_some_and(|value| value != "1")
+        {
+            return self.error_response(
+                Code::InvalidArgument,
+                "unsupported connect protocol version",
+            );
+        }
+        match self {
+            Connect::Unary(codec) => self.unary_response(state, req, codec).await,
+            Connect::Streaming(codec) => self.streaming_response(state, req, codec).await,
+        }
+    }
+
+    /// Serves a unary request.
+    async fn unary_response(
+        self,
+        state: Arc<MockServerState>,
+        req: http::Request<Incoming>,
+        codec: Codec,
+    ) -> http::Response<BoxBody> {
+        let deadline = deadline(req.headers());
+        let (encoding, accept_encodings) =
+            match self.encodings(req.headers(), "content-encoding", "accept-encoding") {
+                Ok(encodings) => encodings,
+                Err(response) => return response,
+            };
+        let (parts, body) = req.into_parts();
+        let handle_request = async {
+            let Ok(body) = body.collect().await.map(|body| body.to_bytes()) else {
+                return self.error_response(Code::Internal, "failed to read request body");
+            };
+            let message = match encoding {
+                Some(encoding) => match encoding.decompress(&body) {
+                    Ok(data) => data.into(),
+                    Err(_) => {
+                        return self.error_response(Code::Internal, "failed to decompress message")
+                    }
+                },
+                None => body,
+            };
+
+            // Match request to mock
+            let messages = vec![message];
+            let request = Request::from_parts(parts)
+                .with_body(request_body(codec, &messages))
+                .with_messages(messages);
+            let Some(mock) = state.match_by_request(&request).await else {
+                debug!(?request, "no mocks found, sending error");
+                return self.error_response(Code::NotFound, "mock not found");
+            };
+            debug!("mock found, sending response");
+            let mut response = mock.response.clone();
+            if let Some(delay) = response.delay() {
+                tokio::time::sleep(delay).await;
+            }
+
+            // Trailing metadata is sent as prefixed headers
+            let mut headers = HeaderMap::from(response.headers().clone());
+            for (name, value) in &HeaderMap::from(response.trailers().clone()) {
+                let name = HeaderName::try_from(format!("trailer-{name}")).unwrap();
+                headers.append(name, value.clone());
+            }
+            let mut res = if response.code() != Code::Ok {
+                let error = error_json(
+                    response.code(),
+                    response.message().unwrap_or_default(),
+                    response.error_details(),
+                );
+                json_response(http_status(response.code()), &error)
+            } else {
+                let messages = match response_messages(codec, response.body.as_bytes()) {
+                    Ok(messages) => messages,
+                    Err(message) => return self.error_response(Code::Internal, &message),
+                };
+                let mut body = messages.into_iter().next().unwrap_or_default();
+                let compression = response
+                    .compression()
+                    .filter(|encoding| accept_encodings.contains(encoding));
+                if let Some(encoding) = compression {
+                    body = match compress(encoding, &body) {
+                        Ok(body) => body,
+                        Err(message) => return self.error_response(Code::Internal, &message),
+                    };
+                    headers.insert(
+                        "content-encoding",
+                        HeaderValue::from_static(encoding.as_str()),
+                    );
+                }
+                http::Response::builder()
+                    .header("content-type", self.content_type())
+                    .body(full(body))
+                    .unwrap()
+            };
+            res.headers_mut().extend(headers);
+            res
+        };
+        // Enforce request deadline
+        match with_deadline(deadline, handle_request).await {
+            Some(response) => response,
+            None => {
+                debug!("deadline exceeded, sending error");
+                self.error_response(Code::DeadlineExceeded, "deadline exceeded")
+            }
+        }
+    }
+
+    /// Serves a streaming request.
+    async fn streaming_response(
+        self,
+        state: Arc<MockServerState>,
+        req: http::Request<Incoming>,
+        codec: Codec,
+    ) -> http::Response<BoxBody> {
+        let deadline = deadline(req.headers());
+        let (encoding, accept_encodings) = match self.encodings(
+            req.headers(),
+            "connect-content-encoding",
+            "connect-accept-encoding",
+        ) {
+            Ok(encodings) => encodings,
+            Err(response) => return response,
+        };
+        let (parts, body) = req.into_parts();
+        let (headers, response_body) = stream::serve(
+            codec,
+            state,
+            parts,
+            body,
+            encoding,
+            accept_encodings,
+            deadline,
+        )
+        .await;
+
+        let mut response = http::Response::builder()
+            .header("content-type", self.content_type())
+            .body(response_body)
+            .unwrap();
+        response
+            .headers_mut()
+            .insert("connect-accept-encoding", accept_header_value());
+        response.headers_mut().extend(headers);
+        response
+    }
+
+    /// Returns the request and accepted response compression encodings.
+    #[allow(clippy::result_large_err)]
+    fn encodings(
+        &self,
+        headers: &HeaderMap,
+        encoding_header: &'static str,
+        accept_header: &'static str,
+    ) -> Result<(Option<CompressionEncoding>, Vec<CompressionEncoding>), http::Response<BoxBody>>
+    {
+        let encoding = match headers.get(encoding_header) {
+            Some(value) => CompressionEncoding::from_header_value(
+                value.to_str().unwrap_or_default(),
+            )
+            .map_err(|err| {
+                let mut response = self.error_response(Code::Unimplemented, &err.to_string());
+                response
+                    .headers_mut()
+                    .insert(accept_header, accept_header_value());
+                response
+            })?,
+            None => None,
+        };
+        let accept_encodings = headers
+            .get(accept_header)
+            .map(|value| {
+                CompressionEncoding::from_accept_header_value(value.to_str().unwrap_or_default())
+            })
+            .unwrap_or_default();
+        Ok((encoding, accept_encodings))
+    }
+
+    /// Returns an error response.
+    fn error_response(&self, code: Code, message: &str) -> http::Response<BoxBody> {
+        let error = error_json(code, message, &[]);
+        match self {
+            Connect::Unary(_) => json_response(http_status(code), &error),
+            Connect::Streaming(_) => http::Response::builder()
+                .header("content-type", self.content_type())
+                .body(full(end_stream(Some(error), None)))
+                .unwrap(),
+        }
+    }
+}
+
+fn accept_header_value() -> HeaderValue {
+    HeaderValue::from_str(&CompressionEncoding::accept_header_value()).unwrap()
+}
+
+/// Returns the request deadline from the `connect-timeout-ms` header.
+fn deadline(headers: &HeaderMap) -> Option<Instant> {
+    headers
+        .get("connect-timeout-ms")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok())
+        .map(|ms| Instant::now() + Duration::from_millis(ms))
+}
+
+/// Returns the request body to match mocks against.
+///
+/// Protobuf messages are length-prefixed, as with gRPC, so the same mocks match
+/// both protocols. JSON messages are concatenated as is.
+fn request_body(codec: Codec, messages: &[Bytes]) -> Body {
+    let mut buf = BytesMut::new();
+    for message in messages {
+        match codec {
+            Codec::Proto => buf.extend(codec::encode_frame(false, message)),
+            Codec::Json => buf.extend_from_slice(message),
+        }
+    }
+    Body::bytes(buf.freeze())
+}
+
+/// Returns the messages of a mock response body.
+///
+/// Length-prefixed messages (e.g. from `Then::pb`) are unwrapped, and cannot
+/// be sent with the JSON codec; any other non-empty body is a single message.
+fn response_messages(codec: Codec, body: Bytes) -> Result<Vec<Bytes>, String> {
+    let mut decoder = GrpcFrameDecoder::new();
+    let frames = decoder.decode(&body);
+    if frames.is_empty() || decoder.has_remaining() {
+        if body.is_empty() {
+            Ok(Vec::new())
+        } else {
+            Ok(vec![body])
+        }
+    } else if codec == Codec::Json {
+        Err("mock response is protobuf, but the request codec is json".to_string())
+    } else {
+        Ok(frames.into_iter().map(|frame| frame.data).collect())
+    }
+}
+
+impl Framing for Codec {
+    fn encoding_header(&self) -> &'static str {
+        "connect-content-encoding"
+    }
+
+    fn request_body(&self, messages: &[Bytes]) -> Body {
+        request_body(*self, messages)
+    }
+
+    fn data_frame(
+        &mut self,
+        chunk: Bytes,
+        compression: Option<CompressionEncoding>,
+    ) -> Result<Frame<Bytes>, String> {
+        let mut buf = BytesMut::new();
+        for message in response_messages(*self, chunk)? {
+            let envelope = match compression {
+                Some(encoding) => codec::encode_frame(true, &compress(encoding, &message)?),
+                None => codec::encode_frame(false, &message),
+            };
+            buf.extend(envelope);
+        }
+        Ok(Frame::data(buf.freeze()))
+    }
+
+    fn response_end_frame(&self, response: &Response) -> Frame<Bytes> {
+        let error = (response.code() != Code::Ok).then(|| {
+            error_json(
+                response.code(),
+                response.message().unwrap_or_default(),
+                response.error_details(),
+            )
+        });
+        Frame::data(end_stream(error, metadata_json(response)))
+    }
+
+    fn error_end_frame(&self, code: Code, message: &str) -> Frame<Bytes> {
+        Frame::data(end_stream(Some(error_json(code, message, &[])), None))
+    }
+}
+
+/// Returns a Connect error JSON object.
+fn error_json(code: Code, message: &str, details: &[ErrorDetail]) -> Value {
+    let mut error = json!({ "code": code_name(code) });
+    if !message.is_empty() {
+        error["message"] = message.into();
+    }
+    if !details.is_empty() {
+        error["details"] = details
+            .iter()
+            .map(|detail| {
+                let any = detail.to_any();
+                let type_name = any
+                    .type_url
+                    .rsplit_once('/')
+                    .map_or(any.type_url.as_str(), |(_, name)| name);
+                json!({ "type": type_name, "value": STANDARD_NO_PAD.encode(&any.value) })
+            })
+            .collect();
+    }
+    error
+}
+
+/// Returns the trailing metadata of a mock response as a JSON object.
+fn metadata_json(response: &Response) -> Option<Value> {
+    let trailers = HeaderMap::from(response.trailers().clone());
+    if trailers.is_empty() {
+        return None;
+    }
+    let mut metadata = serde_json::Map::new();
+    for name in trailers.keys() {
+        let values = trailers
+            .get_all(name)
+            .iter()
+            .map(|value| Value::from(value.to_str().unwrap_or_default()))
+            .collect();
+        metadata.insert(name.to_string(), Value::Array(values));
+    }
+    Some(Value::Object(metadata))
+}
+
+/// Returns an end-of-stream envelope.
+fn end_stream(error: Option<Value>, metadata: Option<Value>) -> Bytes {
+    let mut message = json!({});
+    if let Some(error) = error {
+        message["error"] = error;
+    }
+    if let Some(metadata) = metadata {
+        message["metadata"] = metadata;
+    }
+    let data = serde_json::to_vec(&message).unwrap();
+    let mut buf = BytesMut::with_capacity(HEADER_SIZE + data.len());
+    buf.put_u8(END_STREAM_FLAG);
+    buf.put_u32(data.len() as u32);
+    buf.put_slice(&data);
+    buf.freeze()
+}
+
+fn json_response(status: http::StatusCode, body: &Value) -> http::Response<BoxBody> {
+    let body = Bytes::from(serde_json::to_vec(body).unwrap());
+    http::Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(full(body))
+        .unwrap()
+}
+
+/// Returns the Connect name of a code.
+fn code_name(code: Code) -> &'static str {
+    match code {
+        Code::Ok => "ok",
+        Code::Cancelled => "canceled",
+        Code::Unknown => "unknown",
+        Code::InvalidArgument => "invalid_argument",
+        Code::DeadlineExceeded => "deadline_exceeded",
+        Code::NotFound => "not_found",
+        Code::AlreadyExists => "already_exists",
+        Code::PermissionDenied => "permission_denied",
+        Code::ResourceExhausted => "resource_exhausted",
+        Code::FailedPrecondition => "failed_precondition",
+        Code::Aborted => "aborted",
+        Code::OutOfRange => "out_of_range",
+        Code::Unimplemented => "unimplemented",
+        Code::Internal => "internal",
+        Code::Unavailable => "unavailable",
+        Code::DataLoss => "data_loss",
+        Code::Unauthenticated => "unauthenticated",
+    }
+}
+
+/// Returns the HTTP status of a unary error response.
+fn http_status(code: Code) -> http::StatusCode {
+    match code {
+        Code::Ok => http::StatusCode::OK,
+        Code::Cancelled => http::StatusCode::from_u16(499).unwrap(),
+        Code::InvalidArgument | Code::FailedPrecondition | Code::OutOfRange => {
+            http::StatusCode::BAD_REQUEST
+        }
+        Code::DeadlineExceeded => http::StatusCode::GATEWAY_TIMEOUT,
+        Code::NotFound => http::StatusCode::NOT_FOUND,
+        Code::AlreadyExists | Code::Aborted => http::StatusCode::CONFLICT,
+        Code::PermissionDenied => http::StatusCode::FORBIDDEN,
+        Code::ResourceExhausted => http::StatusCode::TOO_MANY_REQUESTS,
+        Code::Unimplemented => http::StatusCode::NOT_IMPLEMENTED,
+        Code::Unavailable => http::StatusCode::SERVICE_UNAVAILABLE,
+        Code::Unauthenticated => http::StatusCode::UNAUTHORIZED,
+        Code::Unknown | Code::Internal | Code::DataLoss => http::StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
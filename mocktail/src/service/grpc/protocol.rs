@@ -0,0 +1,159 @@
+//! gRPC wire protocols
+//!
+//! Supports native gRPC and [gRPC-Web](https://github.com/grpc/grpc/blob/master/doc/PROTOCOL-WEB.md),
+//! in binary and base64 text mode.
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bytes::{BufMut, Bytes, BytesMut};
+use http::HeaderMap;
+use http_body::Frame;
+
+use crate::grpc::codec::HEADER_SIZE;
+
+/// gRPC-Web trailers frame flag.
+const TRAILERS_FLAG: u8 = 0x80;
+
+/// A gRPC wire protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    /// `application/grpc`
+    Grpc,
+    /// `application/grpc-web`
+    GrpcWeb,
+    /// `application/grpc-web-text`
+    GrpcWebText,
+}
+
+impl Protocol {
+    /// Returns the protocol of a request content-type, if supported.
+    pub fn from_content_type(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if let Some(subtype) = value.strip_prefix("application/grpc-web-text") {
+            (subtype.is_empty() || subtype.starts_with('+')).then_some(Self::GrpcWebText)
+        } else if let Some(subtype) = value.strip_prefix("application/grpc-web") {
+            (subtype.is_empty() || subtype.starts_with('+')).then_some(Self::GrpcWeb)
+        } else if let Some(subtype) = value.strip_prefix("application/grpc") {
+            (subtype.is_empty() || subtype.starts_with('+')).then_some(Self::Grpc)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the response content-type.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Protocol::Grpc => "application/grpc",
+            Protocol::GrpcWeb => "application/grpc-web+proto",
+            Protocol::GrpcWebText => "application/grpc-web-text+proto",
+        }
+    }
+
+    /// Returns a response data frame.
+    pub fn data_frame(&self, data: Bytes) -> Frame<Bytes> {
+        match self {
+            Protocol::GrpcWebText => Frame::data(STANDARD.encode(data).into()),
+            _ => Frame::data(data),
+        }
+    }
+
+    /// Returns a response trailers frame.
+    ///
+    /// gRPC-Web sends trailers as a length-prefixed message with the `0x80` flag set.
+    pub fn trailers_frame(&self, trailers: HeaderMap) -> Frame<Bytes> {
+        match self {
+            Protocol::Grpc => Frame::trailers(trailers),
+            _ => self.data_frame(encode_trailers(&trailers)),
+        }
+    }
+}
+
+/// Encodes trailers as a gRPC-Web trailers frame.
+fn encode_trailers(trailers: &HeaderMap) -> Bytes {
+    let mut block = BytesMut::new();
+    for (name, value) in trailers {
+        block.put_slice(name.as_str().as_bytes());
+        block.put_slice(b": ");
+        block.put_slice(value.as_bytes());
+        block.put_slice(b"\r\n");
+    }
+    let mut buf = BytesMut::with_capacity(HEADER_SIZE + block.len());
+    buf.put_u8(TRAILERS_FLAG);
+    buf.put_u32(block.len() as u32);
+    buf.put_slice(&block);
+    buf.freeze()
+}
+
+/// A decoder for base64 encoded `application/grpc-web-text` request bodies.
+///
+/// The body may be split at any point and may be a concatenation of
+/// separately padded base64 segments.
+#[derive(Debug, Default)]
+pub struct TextDecoder {
+    buf: BytesMut,
+}
+
+impl TextDecoder {
+    /// Adds a chunk of base64 text and returns the decoded data.
+    pub fn decode(&mut self, chunk: &[u8]) -> Result<Bytes, base64::DecodeError> {
+        self.buf
+            .extend(chunk.iter().filter(|b| !b.is_ascii_whitespace()));
+        let len = self.buf.len() - self.buf.len() % 4;
+        let text = self.buf.split_to(len);
+        let mut data = BytesMut::with_capacity(len / 4 * 3);
+        // Decode each quantum separately, as padding may occur mid-stream
+        for quantum in text.chunks_exact(4) {
+            data.extend(STANDARD.decode(quantum)?);
+        }
+        Ok(data.freeze())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_content_type() {
+        assert_eq!(
+            Protocol::from_content_type("application/grpc"),
+            Some(Protocol::Grpc)
+        );
+        assert_eq!(
+            Protocol::from_content_type("application/grpc+proto"),
+            Some(Protocol::Grpc)
+        );
+        assert_eq!(
+            Protocol::from_content_type("application/grpc-web"),
+            Some(Protocol::GrpcWeb)
+        );
+        assert_eq!(
+            Protocol::from_content_type("application/grpc-web+proto"),
+            Some(Protocol::GrpcWeb)
+        );
+        assert_eq!(
+            Protocol::from_content_type("application/grpc-web-text+proto"),
+            Some(Protocol::GrpcWebText)
+        );
+        assert_eq!(Protocol::from_content_type("application/grpcx"), None);
+        assert_eq!(Protocol::from_content_type("application/json"), None);
+    }
+
+    #[test]
+    fn test_text_decoder() {
+        let mut text = STANDARD.encode(b"hello").into_bytes();
+        text.extend(STANDARD.encode(b" world").into_bytes());
+        let mut decoder = TextDecoder::default();
+        let data = text
+            .chunks(3)
+            .flat_map(|chunk| decoder.decode(chunk).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(data, b"hello world");
+    }
+
+    #[test]
+    fn test_encode_trailers() {
+        let mut trailers = HeaderMap::new();
+        trailers.insert("grpc-status", "0".parse().unwrap());
+        let frame = encode_trailers(&trailers);
+        assert_eq!(&frame[..], b"\x80\x00\x00\x00\x10grpc-status: 0\r\n");
+    }
+}
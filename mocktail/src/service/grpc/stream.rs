@@ -0,0 +1,316 @@
+//! Streaming calls
+//!
+//! Serves length-prefixed request and response message streams, shared by the
+//! gRPC and Connect protocols.
+use std::{future::Future, sync::Arc};
+
+use bytes::Bytes;
+use futures::StreamExt;
+use http::{HeaderMap, HeaderValue};
+use http_body::Frame;
+use http_body_util::{BodyExt, StreamBody};
+use hyper::body::Incoming;
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::Instant,
+};
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::debug;
+
+use crate::{
+    body::Body,
+    grpc::{
+        codec::{GrpcFrame, GrpcFrameDecoder},
+        CompressionEncoding,
+    },
+    request::Request,
+    response::Response,
+    server::MockServerState,
+    service::http::BoxBody,
+    Code,
+};
+
+type ResponseSender = mpsc::Sender<Result<Frame<Bytes>, hyper::Error>>;
+
+/// Protocol-specific framing of a streaming call.
+pub trait Framing: Send + Sync + 'static {
+    /// Name of the message encoding header.
+    fn encoding_header(&self) -> &'static str;
+    /// Decodes a chunk of the request body.
+    fn decode_chunk(&mut self, chunk: Bytes) -> Result<Bytes, String> {
+        Ok(chunk)
+    }
+    /// Returns the request body to match mocks against.
+    fn request_body(&self, messages: &[Bytes]) -> Body;
+    /// Returns the data frame of a chunk of a mock response body.
+    fn data_frame(
+        &mut self,
+        chunk: Bytes,
+        compression: Option<CompressionEncoding>,
+    ) -> Result<Frame<Bytes>, String>;
+    /// Returns the data frame of any data buffered at the end of a mock response body.
+    fn end_data_frame(&mut self) -> Option<Frame<Bytes>> {
+        None
+    }
+    /// Returns the frame ending a call with the status of a mock response.
+    fn response_end_frame(&self, response: &Response) -> Frame<Bytes>;
+    /// Returns the frame ending a call with an error status.
+    fn error_end_frame(&self, code: Code, message: &str) -> Frame<Bytes>;
+}
+
+/// Serves a streaming call, returning the response headers and body.
+///
+/// Mocks are matched once the request stream closes, against all of its messages.
+/// If the first message matches a bidi mock, each message is matched separately
+/// to a bidi mock instead, and its response is sent right away. The request body is
+/// then the current message, while the request messages are all messages so far.
+///
+/// Response headers are returned once a mock is matched, as they include its leading metadata.
+pub async fn serve<F: Framing>(
+    mut framing: F,
+    state: Arc<MockServerState>,
+    parts: http::request::Parts,
+    body: Incoming,
+    encoding: Option<CompressionEncoding>,
+    accept_encodings: Vec<CompressionEncoding>,
+    deadline: Option<Instant>,
+) -> (HeaderMap, BoxBody) {
+    let mut stream = body.into_data_stream();
+
+    // Create response stream
+    let (response_tx, response_rx) = mpsc::channel(32);
+    let response_stream = ReceiverStream::new(response_rx);
+    let response_body = BoxBody::new(StreamBody::new(response_stream));
+    let (headers_tx, headers_rx) = oneshot::channel::<HeaderMap>();
+    let mut headers_tx = Some(headers_tx);
+
+    // Spawn task to handle request
+    tokio::spawn(async move {
+        let handle_request = async {
+            let mut request = Request::from_parts(parts);
+            let mut decoder = GrpcFrameDecoder::new();
+            let mut messages = Vec::new();
+
+            // Bidi mocks are matched message by message, other mocks by the whole stream.
+            // The call is bidi if its first message matches a bidi mock.
+            let mut try_bidi = state
+                .mocks()
+                .candidates(&request)
+                .iter()
+                .any(|mock| mock.is_bidi());
+            let mut bidi = false;
+            // Status of the last matched bidi mock, sent when the request stream closes
+            let mut end_frame = None;
+
+            while let Some(Ok(chunk)) = stream.next().await {
+                debug!(?chunk, "received chunk");
+                let chunk = match framing.decode_chunk(chunk) {
+                    Ok(chunk) => chunk,
+                    Err(message) => {
+                        let frame = framing.error_end_frame(Code::Internal, &message);
+                        let _ = response_tx.send(Ok(frame)).await;
+                        return;
+                    }
+                };
+                // Decode complete messages from chunk
+                for frame in decoder.decode(chunk) {
+                    let data = match decompress(encoding, frame, framing.encoding_header()) {
+                        Ok(data) => data,
+                        Err(message) => {
+                            let frame = framing.error_end_frame(Code::Internal, &message);
+                            let _ = response_tx.send(Ok(frame)).await;
+                            return;
+                        }
+                    };
+                    messages.push(data);
+                    if !try_bidi && !bidi {
+                        continue;
+                    }
+
+                    // Match message to a bidi mock, with message indices from the start of the stream
+                    request = request
+                        .with_body(framing.request_body(&messages[messages.len() - 1..]))
+                        .with_messages(messages.clone());
+                    let Some(mock) = state.match_stream_message(&request).await else {
+                        if try_bidi {
+                            // Match the whole stream to other mocks
+                            try_bidi = false;
+                            continue;
+                        }
+                        debug!(?request, "no mocks found for message, sending error");
+                        send_not_found(&framing, &mut headers_tx, &response_tx).await;
+                        return;
+                    };
+                    try_bidi = false;
+                    bidi = true;
+                    debug!("mock found, sending response");
+                    let mut response = mock.response.clone();
+                    let sent = send_response(
+                        &mut framing,
+                        &mut response,
+                        &accept_encodings,
+                        &mut headers_tx,
+                        &response_tx,
+                    )
+                    .await;
+                    if !sent {
+                        return;
+                    }
+                    let frame = framing.response_end_frame(&response);
+                    if response.code() != Code::Ok {
+                        // Error ends the call
+                        let _ = response_tx.send(Ok(frame)).await;
+                        return;
+                    }
+                    end_frame = Some(frame);
+                }
+            }
+            debug!("request stream closed");
+            if bidi {
+                match end_frame {
+                    Some(frame) => {
+                        let _ = response_tx.send(Ok(frame)).await;
+                    }
+                    None => send_not_found(&framing, &mut headers_tx, &response_tx).await,
+                }
+                return;
+            }
+
+            // Match request to mock
+            request = request
+                .with_body(framing.request_body(&messages))
+                .with_messages(messages);
+            let Some(mock) = state.match_by_request(&request).await else {
+                debug!(?request, "no mocks found, sending error");
+                send_not_found(&framing, &mut headers_tx, &response_tx).await;
+                return;
+            };
+            debug!("mock found, sending response");
+            let mut response = mock.response.clone();
+            if send_response(
+                &mut framing,
+                &mut response,
+                &accept_encodings,
+                &mut headers_tx,
+                &response_tx,
+            )
+            .await
+            {
+                let _ = response_tx
+                    .send(Ok(framing.response_end_frame(&response)))
+                    .await;
+            }
+        };
+        // Enforce request deadline
+        if with_deadline(deadline, handle_request).await.is_none() {
+            debug!("deadline exceeded, sending error");
+            if let Some(headers_tx) = headers_tx.take() {
+                let _ = headers_tx.send(HeaderMap::new());
+            }
+            let frame = framing.error_end_frame(Code::DeadlineExceeded, "deadline exceeded");
+            let _ = response_tx.send(Ok(frame)).await;
+        }
+    });
+
+    (headers_rx.await.unwrap_or_default(), response_body)
+}
+
+/// Sends the headers and data frames of a mock response.
+///
+/// Returns `false` if the call was ended with an error.
+async fn send_response<F: Framing>(
+    framing: &mut F,
+    response: &mut Response,
+    accept_encodings: &[CompressionEncoding],
+    headers_tx: &mut Option<oneshot::Sender<HeaderMap>>,
+    response_tx: &ResponseSender,
+) -> bool {
+    let compression = response
+        .compression()
+        .filter(|encoding| accept_encodings.contains(encoding));
+    // Send headers with leading metadata
+    if let Some(headers_tx) = headers_tx.take() {
+        let mut headers = HeaderMap::from(response.headers().clone());
+        if let Some(encoding) = compression {
+            headers.insert(
+                framing.encoding_header(),
+                HeaderValue::from_static(encoding.as_str()),
+            );
+        }
+        let _ = headers_tx.send(headers);
+    }
+    // Delay response messages
+    if let Some(delay) = response.delay() {
+        tokio::time::sleep(delay).await;
+    }
+    // Send data frames
+    while let Some(chunk) = response.body.next().await {
+        match framing.data_frame(chunk, compression) {
+            Ok(frame) => {
+                let _ = response_tx.send(Ok(frame)).await;
+            }
+            Err(message) => {
+                let frame = framing.error_end_frame(Code::Internal, &message);
+                let _ = response_tx.send(Ok(frame)).await;
+                return false;
+            }
+        }
+    }
+    if let Some(frame) = framing.end_data_frame() {
+        let _ = response_tx.send(Ok(frame)).await;
+    }
+    true
+}
+
+/// Ends a call with a mock not found error.
+async fn send_not_found<F: Framing>(
+    framing: &F,
+    headers_tx: &mut Option<oneshot::Sender<HeaderMap>>,
+    response_tx: &ResponseSender,
+) {
+    if let Some(headers_tx) = headers_tx.take() {
+        let _ = headers_tx.send(HeaderMap::new());
+    }
+    let frame = framing.error_end_frame(Code::NotFound, "mock not found");
+    let _ = response_tx.send(Ok(frame)).await;
+}
+
+/// Runs a future to completion, or until the deadline expires.
+pub async fn with_deadline<F: Future>(deadline: Option<Instant>, fut: F) -> Option<F::Output> {
+    match deadline {
+        Some(deadline) => tokio::select! {
+            output = fut => Some(output),
+            _ = tokio::time::sleep_until(deadline) => None,
+        },
+        None => Some(fut.await),
+    }
+}
+
+/// Compresses a response message.
+pub fn compress(encoding: CompressionEncoding, data: &[u8]) -> Result<Bytes, String> {
+    encoding.compress(data).map(Bytes::from).map_err(|err| {
+        debug!(%err, "failed to compress message");
+        "failed to compress message".to_string()
+    })
+}
+
+/// Decompresses a request message.
+pub fn decompress(
+    encoding: Option<CompressionEncoding>,
+    frame: GrpcFrame,
+    encoding_header: &str,
+) -> Result<Bytes, String> {
+    match (frame.compressed, encoding) {
+        (false, _) => Ok(frame.data),
+        (true, Some(encoding)) => {
+            encoding
+                .decompress(&frame.data)
+                .map(Bytes::from)
+                .map_err(|err| {
+                    debug!(%err, "failed to decompress message");
+                    "failed to decompress message".to_string()
+                })
+        }
+        (true, None) => Err(format!("compressed message without {encoding_header}")),
+    }
+}
@@ -1,5 +1,5 @@
 //! Mock HTTP service
-use std::{convert::Infallible, sync::Arc};
+use std::{convert::Infallible, sync::Arc, time::Duration};
 
 use bytes::{Bytes, BytesMut};
 use futures::{future::BoxFuture, StreamExt};
@@ -7,23 +7,17 @@ use http::HeaderMap;
 use http_body::{Body as _, Frame};
 use http_body_util::{BodyExt, Empty, Full, StreamBody};
 use hyper::{body::Incoming, service::Service};
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 use tokio_stream::wrappers::ReceiverStream;
 use tracing::debug;
 
-use crate::{request::Request, server::MockServerState};
+use crate::{cors::Cors, request::Request, server::MockServerState};
+
+mod websocket;
 
 /// A type-erased HTTP body.
 pub type BoxBody = http_body_util::combinators::BoxBody<Bytes, hyper::Error>;
 
-const ALLOWED_METHODS: [http::Method; 5] = [
-    http::Method::GET,
-    http::Method::POST,
-    http::Method::PUT,
-    http::Method::HEAD,
-    http::Method::DELETE,
-];
-
 /// Mock HTTP service.
 #[derive(Debug, Clone)]
 pub struct HttpMockService {
@@ -43,119 +37,212 @@ impl Service<http::Request<Incoming>> for HttpMockService {
 
     fn call(&self, req: http::Request<Incoming>) -> Self::Future {
         let state = self.state.clone();
+        let Some(cors) = state.cors.read().unwrap().clone() else {
+            return Box::pin(handle(state, req));
+        };
         let fut = async move {
-            debug!(?req, "handling request");
-
-            if !ALLOWED_METHODS.contains(req.method()) {
-                return Ok(http::Response::builder()
-                    .status(http::StatusCode::METHOD_NOT_ALLOWED)
-                    .header("Allow", "GET, POST, PUT, HEAD, DELETE")
+            if Cors::is_preflight(req.method(), req.headers()) {
+                debug!(?req, "handling cors preflight request");
+                let mut res = http::Response::builder()
+                    .status(http::StatusCode::NO_CONTENT)
                     .body(empty())
-                    .unwrap());
+                    .unwrap();
+                if let Some(headers) = cors.preflight_headers(req.headers()) {
+                    res.headers_mut().extend(headers);
+                }
+                return Ok(res);
             }
+            let request_headers = req.headers().clone();
+            let mut res = handle(state, req).await?;
+            cors.add_response_headers(&request_headers, res.headers_mut());
+            Ok(res)
+        };
+        Box::pin(fut)
+    }
+}
 
-            let (parts, mut body) = req.into_parts();
+/// Handles a request.
+async fn handle(
+    state: Arc<MockServerState>,
+    mut req: http::Request<Incoming>,
+) -> Result<http::Response<BoxBody>, Infallible> {
+    debug!(?req, "handling request");
 
-            // Get initial data frame
-            let chunk = if !body.is_end_stream() {
-                body.frame().await.unwrap().unwrap().into_data().unwrap() // TODO: handle errors
-            } else {
-                Bytes::default()
+    let on_upgrade = hyper::upgrade::on(&mut req);
+    let (parts, mut body) = req.into_parts();
+    let request = Request::from_parts(parts);
+
+    if request.is_websocket() {
+        return Ok(websocket::websocket_response(state, request, on_upgrade).await);
+    }
+
+    // Get initial data frame
+    let chunk = if !body.is_end_stream() {
+        body.frame().await.unwrap().unwrap().into_data().unwrap() // TODO: handle errors
+    } else {
+        Bytes::default()
+    };
+    debug!(?chunk, "received chunk");
+
+    if body.is_end_stream() {
+        // Process as unary
+        // Match request to mock
+        let request = request.with_body(chunk);
+        let mock = state.match_by_request(&request).await;
+        if let Some(mock) = mock {
+            debug!("mock found, sending response");
+            let response = &mock.response;
+            if let Some(delay) = response.delay() {
+                tokio::time::sleep(delay).await;
+            }
+            let mut body = response.body().clone().as_bytes();
+            if response.is_error() {
+                if let Some(message) = response.message() {
+                    body = Bytes::copy_from_slice(message.as_bytes());
+                }
+            }
+            let status = response.status().as_http();
+            let body = match response.sse_keep_alive() {
+                Some(interval) => keep_alive_body(body, interval),
+                None if !response.trailers().is_empty() => {
+                    body_with_trailers(body, response.trailers().clone().into())
+                }
+                None => full(body),
             };
-            debug!(?chunk, "received chunk");
+            let mut res = http::Response::builder().status(status).body(body).unwrap();
+            *res.headers_mut() = response.headers.clone().into();
+            Ok(res)
+        } else {
+            debug!(?request, "no mocks found, sending error");
+            Ok(http::Response::builder()
+                .status(http::StatusCode::NOT_FOUND)
+                .body(full(Bytes::from("mock not found")))
+                .unwrap())
+        }
+    } else {
+        // Process as streaming
+        let mut stream = body.into_data_stream();
+
+        // Create response stream
+        let (response_tx, response_rx) = mpsc::channel::<Result<Frame<Bytes>, hyper::Error>>(32);
+        let response_stream = ReceiverStream::new(response_rx);
+        let response_body = BoxBody::new(StreamBody::new(response_stream));
+
+        // Create channel to send response status and headers on first match
+        let (headers_tx, headers_rx) = oneshot::channel::<(http::StatusCode, HeaderMap)>();
+        let mut headers_tx = Some(headers_tx);
+
+        // Spawn task to handle request
+        tokio::spawn(async move {
+            let mut request = request;
+            let mut trailers = None;
+            let mut buf = BytesMut::new();
+            buf.extend(chunk);
+
+            while let Some(Ok(chunk)) = stream.next().await {
+                debug!(?chunk, "received chunk");
+                // Add chunk to body buffer
+                buf.extend(chunk);
 
-            if body.is_end_stream() {
-                // Process as unary
                 // Match request to mock
-                let request = Request::from_parts(parts).with_body(chunk);
-                let mock = state.mocks().match_by_request(&request);
+                request = request.with_body(buf.clone().freeze());
+                let mock = state.match_by_request(&request).await;
                 if let Some(mock) = mock {
                     debug!("mock found, sending response");
-                    let response = mock.response;
-                    let mut body = response.body().clone().as_bytes();
-                    if response.is_error() {
-                        if let Some(message) = response.message() {
-                            body = Bytes::copy_from_slice(message.as_bytes());
-                        }
+                    let mut response = mock.response.clone();
+                    if let Some(headers_tx) = headers_tx.take() {
+                        let status = response.status().as_http();
+                        let _ = headers_tx.send((status, response.headers().clone().into()));
                     }
-                    let status = response.status().as_http();
-                    let mut res = http::Response::builder()
-                        .status(status)
-                        .body(full(body))
-                        .unwrap();
-                    *res.headers_mut() = response.headers.into();
-                    Ok(res)
-                } else {
-                    debug!(?request, "no mocks found, sending error");
-                    Ok(http::Response::builder()
-                        .status(http::StatusCode::NOT_FOUND)
-                        .body(full(Bytes::from("mock not found")))
-                        .unwrap())
-                }
-            } else {
-                // Process as streaming
-                let mut stream = body.into_data_stream();
-
-                // Create response stream
-                let (response_tx, response_rx) =
-                    mpsc::channel::<Result<Frame<Bytes>, hyper::Error>>(32);
-                let response_stream = ReceiverStream::new(response_rx);
-                let response_body = BoxBody::new(StreamBody::new(response_stream));
-                let response = http::Response::builder().body(response_body).unwrap();
-
-                // Spawn task to handle request
-                tokio::spawn(async move {
-                    let mut request = Request::from_parts(parts);
-                    let mut matched = false;
-                    let mut buf = BytesMut::new();
-                    buf.extend(chunk);
-
-                    while let Some(Ok(chunk)) = stream.next().await {
-                        debug!(?chunk, "received chunk");
-                        // Add chunk to body buffer
-                        buf.extend(chunk);
-
-                        // Match request to mock
-                        request = request.with_body(buf.clone().freeze());
-                        let mock = state.mocks().match_by_request(&request);
-                        if let Some(mock) = mock {
-                            matched = true;
-                            debug!("mock found, sending response");
-                            let mut response = mock.response;
-                            // Send data frames
-                            if !response.body().is_empty() {
-                                while let Some(chunk) = response.body.next().await {
-                                    let _ = response_tx.send(Ok(Frame::data(chunk))).await;
-                                }
-                            }
-                            if response.is_error() {
-                                let message = response
-                                    .message()
-                                    .map(|s| Bytes::copy_from_slice(s.as_bytes()))
-                                    .unwrap_or_default();
-                                let _ = response_tx.send(Ok(Frame::data(message))).await;
-                            }
-                            // Send trailers frame
-                            let trailers = HeaderMap::from(response.headers().clone());
-                            let _ = response_tx.send(Ok(Frame::trailers(trailers))).await;
-                            // Clear body buffer
-                            buf.clear();
+                    if let Some(delay) = response.delay() {
+                        tokio::time::sleep(delay).await;
+                    }
+                    // Send data frames
+                    if !response.body().is_empty() {
+                        while let Some(chunk) = response.body.next().await {
+                            let _ = response_tx.send(Ok(Frame::data(chunk))).await;
                         }
                     }
-                    debug!("request stream closed");
-                    if !matched {
-                        debug!(?request, "no mocks found, sending error");
-                        // Send data frame with message
-                        let _ = response_tx
-                            .send(Ok(Frame::data("mock not found".into())))
-                            .await;
+                    if response.is_error() {
+                        let message = response
+                            .message()
+                            .map(|s| Bytes::copy_from_slice(s.as_bytes()))
+                            .unwrap_or_default();
+                        let _ = response_tx.send(Ok(Frame::data(message))).await;
                     }
-                });
-                Ok(response)
+                    if let Some(interval) = response.sse_keep_alive() {
+                        // Keep stream open until the client disconnects
+                        send_keep_alive(&response_tx, interval).await;
+                        return;
+                    }
+                    trailers = Some(HeaderMap::from(response.trailers().clone()));
+                    // Clear body buffer
+                    buf.clear();
+                }
             }
-        };
-        Box::pin(fut)
+            debug!("request stream closed");
+            if let Some(headers_tx) = headers_tx.take() {
+                debug!(?request, "no mocks found, sending error");
+                let _ = headers_tx.send((http::StatusCode::NOT_FOUND, HeaderMap::new()));
+                // Send data frame with message
+                let _ = response_tx
+                    .send(Ok(Frame::data("mock not found".into())))
+                    .await;
+            }
+            if let Some(trailers) = trailers {
+                // Send trailers frame
+                let _ = response_tx.send(Ok(Frame::trailers(trailers))).await;
+            }
+        });
+
+        // Wait for response status and headers
+        let (status, headers) = headers_rx
+            .await
+            .unwrap_or((http::StatusCode::INTERNAL_SERVER_ERROR, HeaderMap::new()));
+        let mut response = http::Response::builder()
+            .status(status)
+            .body(response_body)
+            .unwrap();
+        *response.headers_mut() = headers;
+        Ok(response)
+    }
+}
+
+/// Returns a body followed by a trailers frame.
+fn body_with_trailers(body: Bytes, trailers: HeaderMap) -> BoxBody {
+    let frames = [Ok(Frame::data(body)), Ok(Frame::trailers(trailers))];
+    BoxBody::new(StreamBody::new(futures::stream::iter(frames)))
+}
+
+/// Returns a Server-Sent Events body that is kept open with keep-alive comments.
+fn keep_alive_body(body: Bytes, interval: Duration) -> BoxBody {
+    let (response_tx, response_rx) = mpsc::channel::<Result<Frame<Bytes>, hyper::Error>>(32);
+    tokio::spawn(async move {
+        if !body.is_empty() {
+            let _ = response_tx.send(Ok(Frame::data(body))).await;
+        }
+        send_keep_alive(&response_tx, interval).await;
+    });
+    BoxBody::new(StreamBody::new(ReceiverStream::new(response_rx)))
+}
+
+/// Sends Server-Sent Events keep-alive comments at an interval until the client disconnects.
+async fn send_keep_alive(
+    response_tx: &mpsc::Sender<Result<Frame<Bytes>, hyper::Error>>,
+    interval: Duration,
+) {
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {
+                let comment = Bytes::from_static(b": keep-alive\n\n");
+                if response_tx.send(Ok(Frame::data(comment))).await.is_err() {
+                    break;
+                }
+            }
+            _ = response_tx.closed() => break,
+        }
     }
+    debug!("sse stream closed");
 }
 
 pub fn full(data: Bytes) -> BoxBody {
@@ -0,0 +1,188 @@
+//! Mock WebSocket service
+use std::sync::Arc;
+
+use futures::{SinkExt, StreamExt};
+use hyper::upgrade::{OnUpgrade, Upgraded};
+use hyper_util::rt::TokioIo;
+use tokio_tungstenite::{
+    tungstenite::{
+        handshake::derive_accept_key,
+        protocol::{frame::coding::CloseCode, CloseFrame, Role},
+        Message,
+    },
+    WebSocketStream,
+};
+use tracing::debug;
+
+use super::{empty, full, BoxBody};
+use crate::{
+    request::{Method, Request},
+    server::MockServerState,
+    websocket::{WsMessage, WsScript, WsStep},
+};
+
+/// Handles a WebSocket upgrade request.
+pub async fn websocket_response(
+    state: Arc<MockServerState>,
+    request: Request,
+    on_upgrade: OnUpgrade,
+) -> http::Response<BoxBody> {
+    // Validate handshake before matching, so a rejected handshake does not count as a match
+    if request
+        .headers
+        .get("sec-websocket-version")
+        .is_none_or(|version| version.trim() != "13")
+    {
+        return http::Response::builder()
+            .status(http::StatusCode::UPGRADE_REQUIRED)
+            .header("sec-websocket-version", "13")
+            .body(full("unsupported sec-websocket-version".into()))
+            .unwrap();
+    }
+    let accept_key = if request.method == Method::CONNECT {
+        // HTTP/2 extended CONNECT (RFC 8441) has no key
+        None
+    } else {
+        let Some(key) = request.headers.get("sec-websocket-key") else {
+            return http::Response::builder()
+                .status(http::StatusCode::BAD_REQUEST)
+                .body(full("missing sec-websocket-key header".into()))
+                .unwrap();
+        };
+        Some(derive_accept_key(key.as_bytes()))
+    };
+
+    // Match request to mock
+    let Some(mock) = state.match_by_request(&request).await else {
+        debug!(?request, "no mocks found, sending error");
+        return http::Response::builder()
+            .status(http::StatusCode::NOT_FOUND)
+            .body(full("mock not found".into()))
+            .unwrap();
+    };
+    let mut response = mock.response.clone();
+    let Some(script) = response.websocket.take() else {
+        // Upgrade rejected by mock
+        debug!("mock found without websocket, sending response");
+        let mut res = http::Response::builder()
+            .status(response.status().as_http())
+            .body(full(response.body.as_bytes()))
+            .unwrap();
+        *res.headers_mut() = response.headers.into();
+        return res;
+    };
+    debug!("mock found, accepting upgrade");
+
+    let mut res = match accept_key {
+        Some(accept_key) => http::Response::builder()
+            .status(http::StatusCode::SWITCHING_PROTOCOLS)
+            .header("connection", "upgrade")
+            .header("upgrade", "websocket")
+            .header("sec-websocket-accept", accept_key)
+            .body(empty())
+            .unwrap(),
+        // HTTP/2 extended CONNECT (RFC 8441)
+        None => http::Response::builder().body(empty()).unwrap(),
+    };
+    res.headers_mut()
+        .extend(http::HeaderMap::from(response.headers));
+
+    // Spawn task to run conversation
+    tokio::spawn(async move {
+        match on_upgrade.await {
+            Ok(upgraded) => {
+                let ws =
+                    WebSocketStream::from_raw_socket(TokioIo::new(upgraded), Role::Server, None)
+                        .await;
+                run_script(ws, script).await;
+            }
+            Err(err) => debug!("websocket upgrade error: {err}"),
+        }
+    });
+
+    res
+}
+
+/// Runs a scripted conversation, recording inbound messages.
+async fn run_script(mut ws: WebSocketStream<TokioIo<Upgraded>>, script: WsScript) {
+    let received = script.received();
+    for step in script.steps() {
+        match step {
+            WsStep::Expect(matcher) => {
+                let Some(message) = recv(&mut ws).await else {
+                    return;
+                };
+                received.push(message.clone());
+                if !matcher.matches(&message) {
+                    debug!(?message, "unexpected websocket message, closing");
+                    let _ = ws
+                        .close(Some(CloseFrame {
+                            code: CloseCode::Policy,
+                            reason: "unexpected message".into(),
+                        }))
+                        .await;
+                    break;
+                }
+            }
+            WsStep::Send(message) => {
+                if ws.send(message.clone().into()).await.is_err() {
+                    return;
+                }
+                if matches!(message, WsMessage::Close(_)) {
+                    break;
+                }
+            }
+        }
+    }
+    // Receive until the connection is closed
+    while let Some(message) = recv(&mut ws).await {
+        received.push(message);
+    }
+    debug!("websocket closed");
+}
+
+/// Receives the next data or close message.
+///
+/// Pings are answered automatically.
+async fn recv(ws: &mut WebSocketStream<TokioIo<Upgraded>>) -> Option<WsMessage> {
+    loop {
+        match ws.next().await? {
+            Ok(Message::Ping(_) | Message::Pong(_) | Message::Frame(_)) => continue,
+            Ok(message) => return Some(message.into()),
+            Err(err) => {
+                debug!("websocket error: {err}");
+                return None;
+            }
+        }
+    }
+}
+
+impl From<WsMessage> for Message {
+    fn from(value: WsMessage) -> Self {
+        match value {
+            WsMessage::Text(text) => Message::Text(text.into()),
+            WsMessage::Binary(data) => Message::Binary(data),
+            WsMessage::Ping(data) => Message::Ping(data),
+            WsMessage::Pong(data) => Message::Pong(data),
+            WsMessage::Close(frame) => Message::Close(frame.map(|(code, reason)| CloseFrame {
+                code: code.into(),
+                reason: reason.into(),
+            })),
+        }
+    }
+}
+
+impl From<Message> for WsMessage {
+    fn from(value: Message) -> Self {
+        match value {
+            Message::Text(text) => WsMessage::Text(text.to_string()),
+            Message::Binary(data) => WsMessage::Binary(data),
+            Message::Ping(data) => WsMessage::Ping(data),
+            Message::Pong(data) => WsMessage::Pong(data),
+            Message::Close(frame) => {
+                WsMessage::Close(frame.map(|frame| (frame.code.into(), frame.reason.to_string())))
+            }
+            Message::Frame(frame) => WsMessage::Binary(frame.into_payload()),
+        }
+    }
+}
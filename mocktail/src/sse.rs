@@ -0,0 +1,174 @@
+//! Server-Sent Events
+//!
+//! Encodes and parses events as defined in the
+//! [HTML Living Standard](https://html.spec.whatwg.org/multipage/server-sent-events.html#event-stream-interpretation).
+use std::time::Duration;
+
+use bytes::Bytes;
+
+/// A Server-Sent Event.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SseEvent {
+    pub event: Option<String>,
+    pub data: String,
+    pub id: Option<String>,
+    pub retry: Option<Duration>,
+}
+
+impl SseEvent {
+    /// Creates an event with data.
+    pub fn new(data: impl Into<String>) -> Self {
+        Self {
+            data: data.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Creates an event with JSON data.
+    pub fn json(data: impl serde::Serialize) -> Self {
+        Self::new(serde_json::to_string(&data).unwrap())
+    }
+
+    /// Sets the event type.
+    pub fn with_event(mut self, event: impl Into<String>) -> Self {
+        self.event = Some(event.into());
+        self
+    }
+
+    /// Sets the event id.
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets the reconnection time.
+    pub fn with_retry(mut self, retry: Duration) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// Encodes the event to bytes for a `text/event-stream` body.
+    pub fn to_bytes(&self) -> Bytes {
+        let mut buf = String::new();
+        if let Some(event) = &self.event {
+            buf.push_str(&format!("event: {event}\n"));
+        }
+        if let Some(id) = &self.id {
+            buf.push_str(&format!("id: {id}\n"));
+        }
+        if let Some(retry) = self.retry {
+            buf.push_str(&format!("retry: {}\n", retry.as_millis()));
+        }
+        // Each line is a data field, including a trailing empty line
+        for line in self.data.split('\n') {
+            // A CR before LF is part of the line terminator
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            if line.is_empty() {
+                buf.push_str("data:\n");
+            } else {
+                buf.push_str(&format!("data: {line}\n"));
+            }
+        }
+        buf.push('\n');
+        buf.into()
+    }
+}
+
+impl From<&str> for SseEvent {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<String> for SseEvent {
+    fn from(value: String) -> Self {
+        Self::new(value)
+    }
+}
+
+/// Parses the complete events of a `text/event-stream` body.
+///
+/// Comments and incomplete trailing events are ignored.
+pub fn parse(input: &[u8]) -> Vec<SseEvent> {
+    let input = String::from_utf8_lossy(input);
+    let input = input.strip_prefix('\u{feff}').unwrap_or(&input);
+    let mut events = Vec::new();
+    let mut event = SseEvent::default();
+    let mut data: Option<String> = None;
+    let mut lines = input.split('\n').peekable();
+    while let Some(line) = lines.next() {
+        if lines.peek().is_none() {
+            // Incomplete line
+            break;
+        }
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if line.is_empty() {
+            // Dispatch event
+            if let Some(data) = data.take() {
+                event.data = data;
+                events.push(std::mem::take(&mut event));
+            } else {
+                event = SseEvent::default();
+            }
+            continue;
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "" => {} // Comment
+            "event" => event.event = Some(value.into()),
+            "id" => event.id = Some(value.into()),
+            "retry" => {
+                if let Ok(retry) = value.parse() {
+                    event.retry = Some(Duration::from_millis(retry));
+                }
+            }
+            "data" => match &mut data {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => data = Some(value.into()),
+            },
+            _ => {}
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let events = vec![
+            SseEvent::new("hello"),
+            SseEvent::new("line 1\nline 2")
+                .with_event("update")
+                .with_id("2")
+                .with_retry(Duration::from_millis(1500)),
+            SseEvent::json(serde_json::json!({"message": "hi"})),
+        ];
+        let mut body = events
+            .iter()
+            .flat_map(|event| event.to_bytes())
+            .collect::<Vec<_>>();
+        body.extend(b": keep-alive\n\ndata: incomplete\n");
+        assert_eq!(parse(&body), events);
+    }
+
+    #[test]
+    fn test_to_bytes_line_endings() {
+        // Trailing newline is kept as an empty data line
+        let event = SseEvent::new("a\n");
+        assert_eq!(&event.to_bytes()[..], b"data: a\ndata:\n\n");
+        assert_eq!(parse(&event.to_bytes()), [event]);
+        // CRLF is a line terminator
+        let event = SseEvent::new("a\r\nb");
+        assert_eq!(&event.to_bytes()[..], b"data: a\ndata: b\n\n");
+        assert_eq!(parse(&event.to_bytes()), [SseEvent::new("a\nb")]);
+    }
+}
@@ -0,0 +1,177 @@
+//! WebSocket mocking
+//!
+//! A WebSocket mock runs a scripted conversation once the upgrade is accepted:
+//! expected inbound messages, outbound messages and pings, and a close.
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+
+/// A WebSocket message.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WsMessage {
+    Text(String),
+    Binary(Bytes),
+    Ping(Bytes),
+    Pong(Bytes),
+    /// Close with an optional code and reason.
+    Close(Option<(u16, String)>),
+}
+
+impl WsMessage {
+    /// Creates a text message with JSON.
+    pub fn json(value: impl serde::Serialize) -> Self {
+        Self::Text(serde_json::to_string(&value).unwrap())
+    }
+}
+
+/// An inbound WebSocket message matcher.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WsMatcher {
+    /// Any text or binary message.
+    Any,
+    Text(String),
+    Binary(Bytes),
+    /// A text message with equivalent JSON.
+    Json(serde_json::Value),
+}
+
+impl WsMatcher {
+    /// Evaluates a match condition.
+    pub fn matches(&self, message: &WsMessage) -> bool {
+        match (self, message) {
+            (WsMatcher::Any, WsMessage::Text(_) | WsMessage::Binary(_)) => true,
+            (WsMatcher::Text(expected), WsMessage::Text(text)) => expected == text,
+            (WsMatcher::Binary(expected), WsMessage::Binary(data)) => expected == data,
+            (WsMatcher::Json(expected), WsMessage::Text(text)) => {
+                serde_json::from_str::<serde_json::Value>(text)
+                    .is_ok_and(|value| &value == expected)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// A step of a scripted WebSocket conversation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WsStep {
+    /// Waits for an inbound message; the connection is closed with
+    /// a policy violation if it does not match.
+    Expect(WsMatcher),
+    /// Sends an outbound message.
+    Send(WsMessage),
+}
+
+/// A scripted WebSocket conversation.
+///
+/// Once the script completes, inbound messages continue to be received
+/// until the client closes the connection.
+#[derive(Debug, Clone, Default)]
+pub struct WsScript {
+    steps: Vec<WsStep>,
+    received: WsReceived,
+}
+
+impl WsScript {
+    /// Creates an empty script.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expects an inbound message.
+    pub fn expect(mut self, matcher: WsMatcher) -> Self {
+        self.steps.push(WsStep::Expect(matcher));
+        self
+    }
+
+    /// Expects any inbound text or binary message.
+    pub fn expect_any(self) -> Self {
+        self.expect(WsMatcher::Any)
+    }
+
+    /// Expects an inbound text message.
+    pub fn expect_text(self, text: impl Into<String>) -> Self {
+        self.expect(WsMatcher::Text(text.into()))
+    }
+
+    /// Expects an inbound binary message.
+    pub fn expect_binary(self, data: impl Into<Bytes>) -> Self {
+        self.expect(WsMatcher::Binary(data.into()))
+    }
+
+    /// Expects an inbound JSON text message.
+    pub fn expect_json(self, value: impl serde::Serialize) -> Self {
+        self.expect(WsMatcher::Json(serde_json::to_value(value).unwrap()))
+    }
+
+    /// Sends a message.
+    pub fn send(mut self, message: WsMessage) -> Self {
+        self.steps.push(WsStep::Send(message));
+        self
+    }
+
+    /// Sends a text message.
+    pub fn send_text(self, text: impl Into<String>) -> Self {
+        self.send(WsMessage::Text(text.into()))
+    }
+
+    /// Sends a binary message.
+    pub fn send_binary(self, data: impl Into<Bytes>) -> Self {
+        self.send(WsMessage::Binary(data.into()))
+    }
+
+    /// Sends a JSON text message.
+    pub fn send_json(self, value: impl serde::Serialize) -> Self {
+        self.send(WsMessage::json(value))
+    }
+
+    /// Sends a ping.
+    pub fn ping(self, data: impl Into<Bytes>) -> Self {
+        self.send(WsMessage::Ping(data.into()))
+    }
+
+    /// Closes the connection with a code and reason.
+    pub fn close(self, code: u16, reason: impl Into<String>) -> Self {
+        self.send(WsMessage::Close(Some((code, reason.into()))))
+    }
+
+    /// Returns the script steps.
+    pub fn steps(&self) -> &[WsStep] {
+        &self.steps
+    }
+
+    /// Returns a handle to the inbound messages received by this script.
+    pub fn received(&self) -> WsReceived {
+        self.received.clone()
+    }
+}
+
+impl PartialEq for WsScript {
+    fn eq(&self, other: &Self) -> bool {
+        self.steps == other.steps
+    }
+}
+
+/// Inbound messages received by a [`WsScript`], across all of its connections.
+#[derive(Debug, Clone, Default)]
+pub struct WsReceived(Arc<Mutex<Vec<WsMessage>>>);
+
+impl WsReceived {
+    /// Returns the received messages.
+    pub fn messages(&self) -> Vec<WsMessage> {
+        self.0.lock().unwrap().clone()
+    }
+
+    /// Returns the number of received messages.
+    pub fn len(&self) -> usize {
+        self.0.lock().unwrap().len()
+    }
+
+    /// Returns true if no messages have been received.
+    pub fn is_empty(&self) -> bool {
+        self.0.lock().unwrap().is_empty()
+    }
+
+    pub(crate) fn push(&self, message: WsMessage) {
+        self.0.lock().unwrap().push(message);
+    }
+}